@@ -1,22 +1,38 @@
 use std::{net::SocketAddr, sync::Arc};
 
 use anyhow::Result;
-use axum::{Json, Router, extract::State, http::StatusCode, response::IntoResponse, routing::get};
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::get,
+};
+use ethers::types::Address;
 use serde::Serialize;
 use tracing::info;
 
-use crate::engine::TradingBot;
+use crate::engine::{TradingBot, rpc::EndpointStatus};
 
+/// Serves until the bot starts shutting down, letting open requests finish.
 pub async fn run(bot: Arc<TradingBot>, addr: SocketAddr) -> Result<()> {
+    let shutdown_bot = bot.clone();
     let app = Router::new()
         .route("/health", get(health))
         .route("/portfolio", get(portfolio))
+        .route("/strategies", get(strategies))
+        .route("/wallets", get(wallets))
+        .route("/risk/:token", get(risk))
+        .route("/candles", get(sampled_pairs))
+        .route("/candles/:pair", get(candles))
         .with_state(AppState { bot });
 
     info!(%addr, "starting monitoring api");
     axum::serve(tokio::net::TcpListener::bind(addr).await?, app)
-        .await
-        .map_err(Into::into)
+        .with_graceful_shutdown(async move { shutdown_bot.wait_for_shutdown().await })
+        .await?;
+    info!("monitoring api stopped");
+    Ok(())
 }
 
 #[derive(Clone)]
@@ -26,7 +42,11 @@ struct AppState {
 
 async fn health(State(state): State<AppState>) -> impl IntoResponse {
     match state.bot.health_check().await {
-        Ok(ok) => Json(HealthResponse { status: ok }).into_response(),
+        Ok(ok) => Json(HealthResponse {
+            status: ok,
+            endpoints: state.bot.rpc_status(),
+        })
+        .into_response(),
         Err(err) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ErrorResponse {
@@ -42,9 +62,65 @@ async fn portfolio(State(state): State<AppState>) -> impl IntoResponse {
     Json(snapshot).into_response()
 }
 
+async fn strategies(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.bot.strategy_snapshots().await).into_response()
+}
+
+async fn wallets(State(state): State<AppState>) -> impl IntoResponse {
+    match state.bot.wallet_snapshots().await {
+        Ok(wallets) => Json(wallets).into_response(),
+        Err(err) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: err.to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
+
+async fn risk(State(state): State<AppState>, Path(token): Path<Address>) -> impl IntoResponse {
+    match state.bot.evaluate_token(token).await {
+        Ok(reports) if reports.is_empty() => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("no live pools for {token:?}"),
+            }),
+        )
+            .into_response(),
+        Ok(reports) => Json(reports).into_response(),
+        Err(err) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: err.to_string(),
+            }),
+        )
+            .into_response(),
+    }
+}
+
+async fn sampled_pairs(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.bot.sampled_pairs().await).into_response()
+}
+
+async fn candles(State(state): State<AppState>, Path(pair): Path<Address>) -> impl IntoResponse {
+    let candles = state.bot.candles(&pair).await;
+    if candles.is_empty() {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("no price history for {pair:?}"),
+            }),
+        )
+            .into_response();
+    }
+    Json(candles).into_response()
+}
+
 #[derive(Serialize)]
 struct HealthResponse {
     status: String,
+    endpoints: Vec<EndpointStatus>,
 }
 
 #[derive(Serialize)]
@@ -1,35 +1,71 @@
-use std::{collections::BTreeMap, net::SocketAddr, str::FromStr, time::Duration};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    net::SocketAddr,
+    path::PathBuf,
+    str::FromStr,
+    time::Duration,
+};
 
-use anyhow::{Context, Result, anyhow};
+use anyhow::{Context, Result, anyhow, ensure};
 use ethers::types::{Address, Chain, U256};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tracing::warn;
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct RpcConfig {
-    pub http_url: String,
+    /// HTTP endpoints in preference order; reads fail over between them.
+    pub http_urls: Vec<String>,
     #[serde(default)]
     pub ws_url: Option<String>,
     #[serde(default = "RpcConfig::default_poll_interval_ms")]
     pub poll_interval_ms: u64,
+    /// Endpoints that must return the same balance or `eth_call` result.
+    #[serde(default = "RpcConfig::default_quorum")]
+    pub quorum: usize,
+    #[serde(default = "RpcConfig::default_max_retries")]
+    pub max_retries: u32,
+    #[serde(default = "RpcConfig::default_retry_backoff_ms")]
+    pub retry_backoff_ms: u64,
 }
 
 impl RpcConfig {
     const fn default_poll_interval_ms() -> u64 {
         2000
     }
+
+    const fn default_quorum() -> usize {
+        1
+    }
+
+    const fn default_max_retries() -> u32 {
+        3
+    }
+
+    const fn default_retry_backoff_ms() -> u64 {
+        250
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct ExchangeConfig {
-    pub router_address: Address,
+    pub venues: Vec<VenueConfig>,
     #[serde(default = "ExchangeConfig::default_slippage_bps")]
     pub max_slippage_bps: u16,
     #[serde(default = "ExchangeConfig::default_deadline_secs")]
     pub deadline_secs: u64,
     #[serde(default = "ExchangeConfig::default_max_gas_gwei")]
     pub max_gas_price_gwei: u64,
+    #[serde(default = "ExchangeConfig::default_max_price_impact_bps")]
+    pub max_price_impact_bps: u16,
+    #[serde(default = "ExchangeConfig::default_downsize_on_price_impact")]
+    pub downsize_on_price_impact: bool,
     #[serde(default)]
     pub base_tokens: Vec<Address>,
+    #[serde(default)]
+    pub private_relay: Option<PrivateRelayConfig>,
+    #[serde(default)]
+    pub approval_policy: ApprovalPolicy,
 }
 
 impl ExchangeConfig {
@@ -44,18 +80,150 @@ impl ExchangeConfig {
     const fn default_max_gas_gwei() -> u64 {
         200
     }
+
+    const fn default_max_price_impact_bps() -> u16 {
+        250
+    }
+
+    const fn default_downsize_on_price_impact() -> bool {
+        true
+    }
+}
+
+/// Router ABI family a venue speaks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VenueProtocol {
+    UniswapV2,
+    Solidly,
+    UniswapV3,
+}
+
+/// A swap venue on the configured chain, matched against DexScreener's
+/// `dex_id` and, when `factory_address` is set, the pair's own factory.
+#[derive(Debug, Clone, Deserialize)]
+pub struct VenueConfig {
+    pub dex_id: String,
+    pub protocol: VenueProtocol,
+    pub router_address: Address,
+    #[serde(default)]
+    pub factory_address: Option<Address>,
+    #[serde(default)]
+    pub quoter_address: Option<Address>,
+    #[serde(default = "VenueConfig::default_fee_tiers")]
+    pub fee_tiers: Vec<u32>,
+}
+
+impl VenueConfig {
+    fn default_fee_tiers() -> Vec<u32> {
+        vec![100, 500, 3000, 10_000]
+    }
+
+    fn validate(&self) -> Result<()> {
+        match self.protocol {
+            VenueProtocol::Solidly if self.factory_address.is_none() => Err(anyhow!(
+                "venue {} uses the solidly router and needs factory_address",
+                self.dex_id
+            )),
+            VenueProtocol::UniswapV3 if self.quoter_address.is_none() => Err(anyhow!(
+                "venue {} uses uniswap v3 and needs quoter_address",
+                self.dex_id
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// How much of a token the routers are allowed to pull when selling.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum ApprovalPolicy {
+    /// Approve exactly the amount being sold.
+    #[default]
+    Exact,
+    /// Approve a multiple of the amount being sold so partial exits can reuse it.
+    Bounded { multiplier: u32 },
+    /// Approve `U256::MAX` once per token and router.
+    Unlimited,
+}
+
+impl ApprovalPolicy {
+    pub fn allowance_for(&self, amount: U256) -> U256 {
+        match self {
+            Self::Exact => amount,
+            Self::Bounded { multiplier } => amount.saturating_mul(U256::from(*multiplier)),
+            Self::Unlimited => U256::max_value(),
+        }
+    }
+}
+
+/// JSON-RPC flavour spoken by the private relay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum RelayMethod {
+    #[serde(rename = "eth_sendPrivateTransaction")]
+    PrivateTransaction,
+    #[serde(rename = "eth_sendBundle")]
+    Bundle,
+}
+
+impl FromStr for RelayMethod {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            "eth_sendPrivateTransaction" => Ok(Self::PrivateTransaction),
+            "eth_sendBundle" => Ok(Self::Bundle),
+            other => Err(anyhow!("unsupported private relay method {other}")),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
+pub struct PrivateRelayConfig {
+    pub url: String,
+    #[serde(default = "PrivateRelayConfig::default_method")]
+    pub method: RelayMethod,
+    #[serde(default = "PrivateRelayConfig::default_max_blocks")]
+    pub max_blocks: u64,
+    #[serde(default = "PrivateRelayConfig::default_fallback_to_public")]
+    pub fallback_to_public: bool,
+}
+
+impl PrivateRelayConfig {
+    const fn default_method() -> RelayMethod {
+        RelayMethod::PrivateTransaction
+    }
+
+    const fn default_max_blocks() -> u64 {
+        10
+    }
+
+    const fn default_fallback_to_public() -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StrategyConfig {
+    /// Tags the positions this strategy opens.
+    #[serde(default = "StrategyConfig::default_name")]
+    pub id: String,
+    /// Entry and exit rules to trade with, by name.
+    #[serde(default = "StrategyConfig::default_name")]
+    pub name: String,
     pub max_positions: usize,
     pub position_size_eth: f64,
+    /// Largest total entry value of this strategy's open positions, in USD.
+    #[serde(default)]
+    pub budget_usd: Option<f64>,
     #[serde(default)]
     pub blacklisted_tokens: Vec<Address>,
     #[serde(default = "StrategyConfig::default_reward_take_profit_bps")]
     pub take_profit_bps: u32,
     #[serde(default = "StrategyConfig::default_stop_loss_bps")]
     pub stop_loss_bps: u32,
+    /// Momentum is only considered from 5 minutes up; see
+    /// `MomentumConfig::horizon_minutes` for limiting the windows scored.
     #[serde(default = "StrategyConfig::default_price_momentum_window_minutes")]
     pub price_momentum_window_minutes: u64,
     #[serde(default = "StrategyConfig::default_min_liquidity_usd")]
@@ -64,9 +232,21 @@ pub struct StrategyConfig {
     pub min_daily_volume_usd: f64,
     #[serde(default = "StrategyConfig::default_min_age_minutes")]
     pub min_age_minutes: u64,
+    #[serde(default)]
+    pub momentum: MomentumConfig,
+    #[serde(default)]
+    pub indicators: IndicatorConfig,
+    /// Seconds between discovery passes. Exits are checked on every new block
+    /// when a WebSocket endpoint is configured, otherwise on the same cadence.
+    #[serde(default = "StrategyConfig::default_discovery_interval_secs")]
+    pub discovery_interval_secs: u64,
 }
 
 impl StrategyConfig {
+    fn default_name() -> String {
+        "momentum".to_string()
+    }
+
     const fn default_reward_take_profit_bps() -> u32 {
         2500
     }
@@ -87,9 +267,200 @@ impl StrategyConfig {
         250_000.0
     }
 
+    const fn default_discovery_interval_secs() -> u64 {
+        30
+    }
+
     const fn default_min_age_minutes() -> u64 {
         45
     }
+
+    /// Configured momentum windows longer than the momentum horizon, which
+    /// entries ignore.
+    pub fn ignored_momentum_windows(&self) -> BTreeSet<MarketWindow> {
+        self.momentum
+            .price_weights
+            .keys()
+            .chain(&self.momentum.buy_pressure_windows)
+            .filter(|window| !self.momentum.includes(**window))
+            .copied()
+            .collect()
+    }
+}
+
+/// Indicator periods (in candles of the price history), optional entry
+/// filters and ATR-scaled stops. Every filter is off by default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndicatorConfig {
+    #[serde(default = "IndicatorConfig::default_rsi_period")]
+    pub rsi_period: usize,
+    #[serde(default = "IndicatorConfig::default_ema_short_period")]
+    pub ema_short_period: usize,
+    #[serde(default = "IndicatorConfig::default_ema_long_period")]
+    pub ema_long_period: usize,
+    #[serde(default = "IndicatorConfig::default_atr_period")]
+    pub atr_period: usize,
+    #[serde(default)]
+    pub min_rsi: Option<f64>,
+    #[serde(default)]
+    pub max_rsi: Option<f64>,
+    /// Short EMA must be above the long EMA.
+    #[serde(default)]
+    pub require_ema_trend: bool,
+    /// Latest close must be above the short EMA.
+    #[serde(default)]
+    pub require_above_ema: bool,
+    /// Largest premium of the close over VWAP, in percent.
+    #[serde(default)]
+    pub max_vwap_deviation_pct: Option<f64>,
+    /// Stop distance in ATRs, replacing `stop_loss_bps` for new positions;
+    /// `0` keeps the fixed stop.
+    #[serde(default)]
+    pub atr_stop_multiplier: f64,
+    #[serde(default = "IndicatorConfig::default_atr_stop_min_bps")]
+    pub atr_stop_min_bps: u32,
+}
+
+impl IndicatorConfig {
+    const fn default_rsi_period() -> usize {
+        14
+    }
+
+    const fn default_ema_short_period() -> usize {
+        9
+    }
+
+    const fn default_ema_long_period() -> usize {
+        21
+    }
+
+    const fn default_atr_period() -> usize {
+        14
+    }
+
+    const fn default_atr_stop_min_bps() -> u32 {
+        300
+    }
+}
+
+impl Default for IndicatorConfig {
+    fn default() -> Self {
+        Self {
+            rsi_period: Self::default_rsi_period(),
+            ema_short_period: Self::default_ema_short_period(),
+            ema_long_period: Self::default_ema_long_period(),
+            atr_period: Self::default_atr_period(),
+            min_rsi: None,
+            max_rsi: None,
+            require_ema_trend: false,
+            require_above_ema: false,
+            max_vwap_deviation_pct: None,
+            atr_stop_multiplier: 0.0,
+            atr_stop_min_bps: Self::default_atr_stop_min_bps(),
+        }
+    }
+}
+
+/// DexScreener's rolling statistics windows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarketWindow {
+    M5,
+    M15,
+    H1,
+    H6,
+    H24,
+}
+
+impl MarketWindow {
+    pub const fn minutes(self) -> u64 {
+        match self {
+            Self::M5 => 5,
+            Self::M15 => 15,
+            Self::H1 => 60,
+            Self::H6 => 360,
+            Self::H24 => 1440,
+        }
+    }
+}
+
+impl FromStr for MarketWindow {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "m5" => Ok(Self::M5),
+            "m15" => Ok(Self::M15),
+            "h1" => Ok(Self::H1),
+            "h6" => Ok(Self::H6),
+            "h24" => Ok(Self::H24),
+            other => Err(anyhow!("unknown market window {other}")),
+        }
+    }
+}
+
+/// Entry momentum model.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MomentumConfig {
+    /// Weight of each window's price change (percent) in the momentum score.
+    #[serde(default = "MomentumConfig::default_price_weights")]
+    pub price_weights: BTreeMap<MarketWindow, f64>,
+    #[serde(default = "MomentumConfig::default_min_score")]
+    pub min_score: f64,
+    /// Windows whose buy share of transactions must reach `min_buy_pressure`.
+    #[serde(default = "MomentumConfig::default_buy_pressure_windows")]
+    pub buy_pressure_windows: Vec<MarketWindow>,
+    #[serde(default = "MomentumConfig::default_min_buy_pressure")]
+    pub min_buy_pressure: f64,
+    /// Last hour's volume over the hourly average of the last six hours;
+    /// `0` disables the check.
+    #[serde(default)]
+    pub min_volume_acceleration: f64,
+    /// When set, windows longer than this many minutes are left out of the
+    /// score and the buy pressure check.
+    #[serde(default)]
+    pub horizon_minutes: Option<u64>,
+}
+
+impl Default for MomentumConfig {
+    fn default() -> Self {
+        Self {
+            price_weights: Self::default_price_weights(),
+            min_score: Self::default_min_score(),
+            buy_pressure_windows: Self::default_buy_pressure_windows(),
+            min_buy_pressure: Self::default_min_buy_pressure(),
+            min_volume_acceleration: 0.0,
+            horizon_minutes: None,
+        }
+    }
+}
+
+impl MomentumConfig {
+    /// Whether `window` fits within the horizon.
+    pub fn includes(&self, window: MarketWindow) -> bool {
+        self.horizon_minutes
+            .is_none_or(|horizon| window.minutes() <= horizon)
+    }
+
+    fn default_price_weights() -> BTreeMap<MarketWindow, f64> {
+        BTreeMap::from([
+            (MarketWindow::M5, 0.4),
+            (MarketWindow::M15, 0.35),
+            (MarketWindow::H1, 0.25),
+        ])
+    }
+
+    const fn default_min_score() -> f64 {
+        8.0
+    }
+
+    fn default_buy_pressure_windows() -> Vec<MarketWindow> {
+        vec![MarketWindow::M5]
+    }
+
+    const fn default_min_buy_pressure() -> f64 {
+        0.55
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -102,6 +473,17 @@ pub struct RiskHeuristicsConfig {
     pub min_holder_count: u64,
     #[serde(default = "RiskHeuristicsConfig::default_min_renounced_score")]
     pub min_renounced_score: f64,
+    /// Risk score a token needs, on top of having no critical flags, to be
+    /// considered safe, when the risk policy's `pass_score` refers to it.
+    #[serde(default = "RiskHeuristicsConfig::default_min_safety_score")]
+    pub min_safety_score: f64,
+    /// TOML risk policy replacing the built-in scoring rules.
+    #[serde(default)]
+    pub policy_file: Option<PathBuf>,
+    /// Seconds between risk re-evaluations of held tokens; `0` disables the
+    /// monitor.
+    #[serde(default = "RiskHeuristicsConfig::default_monitor_interval_secs")]
+    pub monitor_interval_secs: u64,
 }
 
 impl RiskHeuristicsConfig {
@@ -120,6 +502,14 @@ impl RiskHeuristicsConfig {
     const fn default_min_renounced_score() -> f64 {
         0.5
     }
+
+    const fn default_min_safety_score() -> f64 {
+        2.8
+    }
+
+    const fn default_monitor_interval_secs() -> u64 {
+        300
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Default)]
@@ -130,6 +520,42 @@ pub struct AlertingConfig {
     pub email_recipients: Vec<String>,
 }
 
+/// Where the trading key lives. Private keys are never stored in the config
+/// itself; keystore passwords are read when the signer is built.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SignerConfig {
+    /// Plaintext hex key in `TRADING_PRIVATE_KEY`.
+    #[default]
+    PrivateKeyEnv,
+    /// Encrypted JSON keystores, one wallet each, unlocked with a shared
+    /// password file or an interactive prompt per keystore.
+    Keystore {
+        paths: Vec<PathBuf>,
+        #[serde(default)]
+        password_file: Option<PathBuf>,
+    },
+    /// `count` wallets derived from the BIP-39 phrase in `TRADING_MNEMONIC`
+    /// along the default `m/44'/60'/0'/0/{index}` path.
+    Mnemonic {
+        #[serde(default = "SignerConfig::default_wallet_count")]
+        count: u32,
+    },
+    /// External signing process reached over JSON-RPC. An empty address list
+    /// signs with the first account the signer exposes.
+    Remote {
+        url: String,
+        #[serde(default)]
+        addresses: Vec<Address>,
+    },
+}
+
+impl SignerConfig {
+    const fn default_wallet_count() -> u32 {
+        1
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct MonitoringConfig {
     #[serde(default = "MonitoringConfig::default_bind_addr")]
@@ -150,6 +576,227 @@ impl Default for MonitoringConfig {
     }
 }
 
+/// Own price history: reserve sampling of tracked pairs into OHLCV candles.
+#[derive(Debug, Clone, Deserialize)]
+pub struct HistoryConfig {
+    /// Seconds between samples; `0` disables the sampler.
+    #[serde(default = "HistoryConfig::default_sample_interval_secs")]
+    pub sample_interval_secs: u64,
+    #[serde(default = "HistoryConfig::default_candle_secs")]
+    pub candle_secs: u64,
+    /// Candles kept per pair.
+    #[serde(default = "HistoryConfig::default_max_candles")]
+    pub max_candles: usize,
+    /// Minutes a discovered pair keeps being sampled after it was last seen.
+    #[serde(default = "HistoryConfig::default_track_minutes")]
+    pub track_minutes: u64,
+}
+
+impl HistoryConfig {
+    const fn default_sample_interval_secs() -> u64 {
+        15
+    }
+
+    const fn default_candle_secs() -> u64 {
+        60
+    }
+
+    const fn default_max_candles() -> usize {
+        720
+    }
+
+    const fn default_track_minutes() -> u64 {
+        240
+    }
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            sample_interval_secs: Self::default_sample_interval_secs(),
+            candle_secs: Self::default_candle_secs(),
+            max_candles: Self::default_max_candles(),
+            track_minutes: Self::default_track_minutes(),
+        }
+    }
+}
+
+/// Where the USD price of one base token is read on-chain.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum PriceSource {
+    /// A Chainlink aggregator quoting the base token in USD.
+    Chainlink { aggregator: Address },
+    /// A pool pairing the base token with a USD stablecoin, taken at $1.
+    StablePair {
+        pair: Address,
+        stablecoin: Address,
+        /// Read `slot0` instead of the reserves.
+        #[serde(default)]
+        concentrated: bool,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PriceFeedConfig {
+    pub base_token: Address,
+    #[serde(flatten)]
+    pub source: PriceSource,
+}
+
+/// On-chain base token pricing, with DefiLlama as an optional cross-check.
+#[derive(Debug, Clone, Deserialize)]
+pub struct OracleConfig {
+    /// Base tokens without a feed are priced by DefiLlama alone.
+    #[serde(default)]
+    pub feeds: Vec<PriceFeedConfig>,
+    /// Compare on-chain prices with DefiLlama, and fall back to it when the
+    /// on-chain read fails.
+    #[serde(default = "OracleConfig::default_cross_check")]
+    pub cross_check: bool,
+    /// Divergence from DefiLlama that raises an alarm.
+    #[serde(default = "OracleConfig::default_max_divergence_bps")]
+    pub max_divergence_bps: f64,
+    /// Age of a Chainlink answer, or of the last good price reused after
+    /// every source failed, that raises an alarm.
+    #[serde(default = "OracleConfig::default_max_age_secs")]
+    pub max_age_secs: u64,
+}
+
+impl OracleConfig {
+    const fn default_cross_check() -> bool {
+        true
+    }
+
+    const fn default_max_divergence_bps() -> f64 {
+        200.0
+    }
+
+    const fn default_max_age_secs() -> u64 {
+        3600
+    }
+}
+
+impl Default for OracleConfig {
+    fn default() -> Self {
+        Self {
+            feeds: Vec::new(),
+            cross_check: Self::default_cross_check(),
+            max_divergence_bps: Self::default_max_divergence_bps(),
+            max_age_secs: Self::default_max_age_secs(),
+        }
+    }
+}
+
+/// Base URLs of the external APIs, without a trailing slash. Pointing them
+/// at `mock-api` runs the bot against fixtures.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiConfig {
+    #[serde(default = "ApiConfig::default_dexscreener_url")]
+    pub dexscreener_url: String,
+    #[serde(default = "ApiConfig::default_goplus_url")]
+    pub goplus_url: String,
+    #[serde(default = "ApiConfig::default_defillama_url")]
+    pub defillama_url: String,
+}
+
+impl ApiConfig {
+    fn default_dexscreener_url() -> String {
+        "https://api.dexscreener.com".to_string()
+    }
+
+    fn default_goplus_url() -> String {
+        "https://api.gopluslabs.io".to_string()
+    }
+
+    fn default_defillama_url() -> String {
+        "https://coins.llama.fi".to_string()
+    }
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            dexscreener_url: Self::default_dexscreener_url(),
+            goplus_url: Self::default_goplus_url(),
+            defillama_url: Self::default_defillama_url(),
+        }
+    }
+}
+
+/// Shared HTTP layer for the DexScreener, GoPlus and DefiLlama APIs.
+#[derive(Debug, Clone, Deserialize)]
+pub struct HttpConfig {
+    /// Requests per second per host, unless overridden in `host_rate_limits`.
+    #[serde(default = "HttpConfig::default_rate_limit_per_sec")]
+    pub rate_limit_per_sec: f64,
+    #[serde(default = "HttpConfig::default_host_rate_limits")]
+    pub host_rate_limits: BTreeMap<String, f64>,
+    /// Requests a host may take in a burst after being idle.
+    #[serde(default = "HttpConfig::default_burst")]
+    pub burst: u32,
+    /// Retries after a 429, a 5xx or a transport error.
+    #[serde(default = "HttpConfig::default_max_retries")]
+    pub max_retries: u32,
+    #[serde(default = "HttpConfig::default_retry_backoff_ms")]
+    pub retry_backoff_ms: u64,
+    /// Seconds a GoPlus report is reused; `0` disables caching.
+    #[serde(default = "HttpConfig::default_security_cache_secs")]
+    pub security_cache_secs: u64,
+    /// Seconds DexScreener pair listings are reused; `0` disables caching.
+    #[serde(default = "HttpConfig::default_market_cache_secs")]
+    pub market_cache_secs: u64,
+    /// Keeps the response cache across restarts.
+    #[serde(default)]
+    pub cache_file: Option<PathBuf>,
+}
+
+impl HttpConfig {
+    const fn default_rate_limit_per_sec() -> f64 {
+        4.0
+    }
+
+    /// GoPlus allows about 30 calls a minute without an API key.
+    fn default_host_rate_limits() -> BTreeMap<String, f64> {
+        BTreeMap::from([("api.gopluslabs.io".to_string(), 0.5)])
+    }
+
+    const fn default_burst() -> u32 {
+        4
+    }
+
+    const fn default_max_retries() -> u32 {
+        3
+    }
+
+    const fn default_retry_backoff_ms() -> u64 {
+        500
+    }
+
+    const fn default_security_cache_secs() -> u64 {
+        900
+    }
+
+    const fn default_market_cache_secs() -> u64 {
+        10
+    }
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            rate_limit_per_sec: Self::default_rate_limit_per_sec(),
+            host_rate_limits: Self::default_host_rate_limits(),
+            burst: Self::default_burst(),
+            max_retries: Self::default_max_retries(),
+            retry_backoff_ms: Self::default_retry_backoff_ms(),
+            security_cache_secs: Self::default_security_cache_secs(),
+            market_cache_secs: Self::default_market_cache_secs(),
+            cache_file: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct BotConfig {
     pub chain: Chain,
@@ -163,6 +810,19 @@ pub struct BotConfig {
     pub alerting: AlertingConfig,
     #[serde(default)]
     pub monitoring: MonitoringConfig,
+    #[serde(default)]
+    pub history: HistoryConfig,
+    #[serde(default)]
+    pub signer: SignerConfig,
+    #[serde(default)]
+    pub oracle: OracleConfig,
+    #[serde(default)]
+    pub api: ApiConfig,
+    #[serde(default)]
+    pub http: HttpConfig,
+    /// Overrides of `strategy`, one per strategy to run side by side.
+    #[serde(default)]
+    pub strategy_profiles: Vec<Map<String, Value>>,
 }
 
 impl BotConfig {
@@ -178,17 +838,36 @@ impl BotConfig {
             Chain::try_from(chain_id).map_err(|_| anyhow!("unsupported chain_id {chain_id}"))?;
 
         let rpc = RpcConfig {
-            http_url: std::env::var("RPC_HTTP").context("RPC_HTTP env var missing")?,
-            ws_url: std::env::var("RPC_WS").ok(),
+            http_urls: std::env::var("RPC_HTTP")
+                .context("RPC_HTTP env var missing")?
+                .split(',')
+                .map(str::trim)
+                .filter(|url| !url.is_empty())
+                .map(str::to_string)
+                .collect(),
+            ws_url: std::env::var("RPC_WS")
+                .ok()
+                .filter(|url| !url.trim().is_empty()),
             poll_interval_ms: std::env::var("RPC_POLL_INTERVAL_MS")
                 .ok()
                 .and_then(|s| s.parse::<u64>().ok())
                 .unwrap_or_else(RpcConfig::default_poll_interval_ms),
+            quorum: std::env::var("RPC_QUORUM")
+                .ok()
+                .and_then(|s| s.parse::<usize>().ok())
+                .unwrap_or_else(RpcConfig::default_quorum),
+            max_retries: std::env::var("RPC_MAX_RETRIES")
+                .ok()
+                .and_then(|s| s.parse::<u32>().ok())
+                .unwrap_or_else(RpcConfig::default_max_retries),
+            retry_backoff_ms: std::env::var("RPC_RETRY_BACKOFF_MS")
+                .ok()
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or_else(RpcConfig::default_retry_backoff_ms),
         };
-
-        let router_address = std::env::var("ROUTER_ADDRESS")
-            .context("ROUTER_ADDRESS env var missing")
-            .and_then(|addr| Address::from_str(&addr).context("invalid ROUTER_ADDRESS"))?;
+        if rpc.http_urls.is_empty() {
+            return Err(anyhow!("RPC_HTTP lists no endpoints"));
+        }
 
         let base_tokens = std::env::var("BASE_TOKENS")
             .unwrap_or_default()
@@ -203,8 +882,45 @@ impl BotConfig {
             })
             .collect::<Vec<_>>();
 
+        let venues = load_venues()?;
+
+        let private_relay = match std::env::var("PRIVATE_RELAY_URL")
+            .ok()
+            .filter(|url| !url.trim().is_empty())
+        {
+            Some(url) => Some(PrivateRelayConfig {
+                url,
+                method: std::env::var("PRIVATE_RELAY_METHOD")
+                    .ok()
+                    .map(|method| method.parse::<RelayMethod>())
+                    .transpose()?
+                    .unwrap_or_else(PrivateRelayConfig::default_method),
+                max_blocks: std::env::var("PRIVATE_RELAY_MAX_BLOCKS")
+                    .ok()
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .unwrap_or_else(PrivateRelayConfig::default_max_blocks),
+                fallback_to_public: std::env::var("PRIVATE_RELAY_FALLBACK")
+                    .ok()
+                    .and_then(|v| v.parse::<bool>().ok())
+                    .unwrap_or_else(PrivateRelayConfig::default_fallback_to_public),
+            }),
+            None => None,
+        };
+
+        let approval_policy = match std::env::var("APPROVAL_POLICY").unwrap_or_default().trim() {
+            "" | "exact" => ApprovalPolicy::Exact,
+            "bounded" => ApprovalPolicy::Bounded {
+                multiplier: std::env::var("APPROVAL_BOUND_MULTIPLIER")
+                    .ok()
+                    .and_then(|v| v.parse::<u32>().ok())
+                    .unwrap_or(2),
+            },
+            "unlimited" => ApprovalPolicy::Unlimited,
+            other => return Err(anyhow!("unsupported APPROVAL_POLICY {other}")),
+        };
+
         let exchange = ExchangeConfig {
-            router_address,
+            venues,
             max_slippage_bps: std::env::var("MAX_SLIPPAGE_BPS")
                 .ok()
                 .and_then(|v| v.parse::<u16>().ok())
@@ -217,10 +933,122 @@ impl BotConfig {
                 .ok()
                 .and_then(|v| v.parse::<u64>().ok())
                 .unwrap_or_else(ExchangeConfig::default_max_gas_gwei),
+            max_price_impact_bps: std::env::var("MAX_PRICE_IMPACT_BPS")
+                .ok()
+                .and_then(|v| v.parse::<u16>().ok())
+                .unwrap_or_else(ExchangeConfig::default_max_price_impact_bps),
+            downsize_on_price_impact: std::env::var("DOWNSIZE_ON_PRICE_IMPACT")
+                .ok()
+                .and_then(|v| v.parse::<bool>().ok())
+                .unwrap_or_else(ExchangeConfig::default_downsize_on_price_impact),
             base_tokens,
+            private_relay,
+            approval_policy,
         };
 
+        let momentum = MomentumConfig {
+            price_weights: match std::env::var("MOMENTUM_WEIGHTS")
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+            {
+                Some(weights) => weights
+                    .split(',')
+                    .filter(|entry| !entry.trim().is_empty())
+                    .map(|entry| {
+                        let (window, weight) = entry.split_once('=').ok_or_else(|| {
+                            anyhow!("MOMENTUM_WEIGHTS entry {entry} is not window=weight")
+                        })?;
+                        Ok((
+                            window.parse::<MarketWindow>()?,
+                            weight
+                                .trim()
+                                .parse::<f64>()
+                                .with_context(|| format!("MOMENTUM_WEIGHTS weight for {window}"))?,
+                        ))
+                    })
+                    .collect::<Result<BTreeMap<_, _>>>()?,
+                None => MomentumConfig::default_price_weights(),
+            },
+            min_score: std::env::var("MIN_MOMENTUM_SCORE")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or_else(MomentumConfig::default_min_score),
+            buy_pressure_windows: match std::env::var("BUY_PRESSURE_WINDOWS")
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+            {
+                Some(windows) => windows
+                    .split(',')
+                    .filter(|window| !window.trim().is_empty())
+                    .map(str::parse::<MarketWindow>)
+                    .collect::<Result<Vec<_>>>()?,
+                None => MomentumConfig::default_buy_pressure_windows(),
+            },
+            min_buy_pressure: std::env::var("MIN_BUY_PRESSURE")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or_else(MomentumConfig::default_min_buy_pressure),
+            min_volume_acceleration: std::env::var("MIN_VOLUME_ACCELERATION")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or_default(),
+            horizon_minutes: std::env::var("MOMENTUM_HORIZON_MINUTES")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok()),
+        };
+
+        let indicators = IndicatorConfig {
+            rsi_period: std::env::var("RSI_PERIOD")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or_else(IndicatorConfig::default_rsi_period),
+            ema_short_period: std::env::var("EMA_SHORT_PERIOD")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or_else(IndicatorConfig::default_ema_short_period),
+            ema_long_period: std::env::var("EMA_LONG_PERIOD")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or_else(IndicatorConfig::default_ema_long_period),
+            atr_period: std::env::var("ATR_PERIOD")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or_else(IndicatorConfig::default_atr_period),
+            min_rsi: std::env::var("ENTRY_MIN_RSI")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok()),
+            max_rsi: std::env::var("ENTRY_MAX_RSI")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok()),
+            require_ema_trend: std::env::var("ENTRY_REQUIRE_EMA_TREND")
+                .ok()
+                .and_then(|v| v.parse::<bool>().ok())
+                .unwrap_or_default(),
+            require_above_ema: std::env::var("ENTRY_REQUIRE_ABOVE_EMA")
+                .ok()
+                .and_then(|v| v.parse::<bool>().ok())
+                .unwrap_or_default(),
+            max_vwap_deviation_pct: std::env::var("ENTRY_MAX_VWAP_DEVIATION_PCT")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok()),
+            atr_stop_multiplier: std::env::var("ATR_STOP_MULTIPLIER")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or_default(),
+            atr_stop_min_bps: std::env::var("ATR_STOP_MIN_BPS")
+                .ok()
+                .and_then(|v| v.parse::<u32>().ok())
+                .unwrap_or_else(IndicatorConfig::default_atr_stop_min_bps),
+        };
+
+        let strategy_name = std::env::var("STRATEGY")
+            .ok()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(StrategyConfig::default_name);
         let strategy = StrategyConfig {
+            id: strategy_name.clone(),
+            name: strategy_name,
             max_positions: std::env::var("MAX_POSITIONS")
                 .ok()
                 .and_then(|v| v.parse::<usize>().ok())
@@ -229,6 +1057,9 @@ impl BotConfig {
                 .ok()
                 .and_then(|v| v.parse::<f64>().ok())
                 .unwrap_or(0.3),
+            budget_usd: std::env::var("STRATEGY_BUDGET_USD")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok()),
             blacklisted_tokens: std::env::var("BLACKLISTED_TOKENS")
                 .unwrap_or_default()
                 .split(',')
@@ -265,6 +1096,13 @@ impl BotConfig {
                 .ok()
                 .and_then(|v| v.parse::<u64>().ok())
                 .unwrap_or_else(StrategyConfig::default_min_age_minutes),
+            momentum,
+            indicators,
+            discovery_interval_secs: std::env::var("DISCOVERY_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .filter(|secs| *secs > 0)
+                .unwrap_or_else(StrategyConfig::default_discovery_interval_secs),
         };
 
         let risk = RiskHeuristicsConfig {
@@ -284,6 +1122,18 @@ impl BotConfig {
                 .ok()
                 .and_then(|v| v.parse::<f64>().ok())
                 .unwrap_or_else(RiskHeuristicsConfig::default_min_renounced_score),
+            min_safety_score: std::env::var("MIN_SAFETY_SCORE")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or_else(RiskHeuristicsConfig::default_min_safety_score),
+            policy_file: std::env::var("RISK_POLICY_FILE")
+                .ok()
+                .filter(|path| !path.trim().is_empty())
+                .map(PathBuf::from),
+            monitor_interval_secs: std::env::var("RISK_MONITOR_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or_else(RiskHeuristicsConfig::default_monitor_interval_secs),
         };
 
         let metadata = std::env::var("BOT_TAGS")
@@ -312,7 +1162,190 @@ impl BotConfig {
                 .unwrap_or_else(MonitoringConfig::default_bind_addr),
         };
 
-        Ok(Self {
+        let history = HistoryConfig {
+            sample_interval_secs: std::env::var("PRICE_SAMPLE_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or_else(HistoryConfig::default_sample_interval_secs),
+            candle_secs: std::env::var("CANDLE_SECS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .filter(|secs| *secs > 0)
+                .unwrap_or_else(HistoryConfig::default_candle_secs),
+            max_candles: std::env::var("CANDLE_HISTORY")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .filter(|count| *count > 0)
+                .unwrap_or_else(HistoryConfig::default_max_candles),
+            track_minutes: std::env::var("PRICE_TRACK_MINUTES")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or_else(HistoryConfig::default_track_minutes),
+        };
+
+        let oracle = OracleConfig {
+            feeds: match std::env::var("PRICE_FEEDS_FILE")
+                .ok()
+                .filter(|path| !path.trim().is_empty())
+            {
+                Some(path) => {
+                    let data = std::fs::read_to_string(&path)
+                        .with_context(|| format!("read price feeds file {path}"))?;
+                    serde_json::from_str(&data)
+                        .with_context(|| format!("parse price feeds file {path}"))?
+                }
+                None => Vec::new(),
+            },
+            cross_check: std::env::var("PRICE_CROSS_CHECK")
+                .ok()
+                .and_then(|v| v.parse::<bool>().ok())
+                .unwrap_or_else(OracleConfig::default_cross_check),
+            max_divergence_bps: std::env::var("PRICE_MAX_DIVERGENCE_BPS")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .filter(|bps| *bps > 0.0)
+                .unwrap_or_else(OracleConfig::default_max_divergence_bps),
+            max_age_secs: std::env::var("PRICE_MAX_AGE_SECS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .filter(|secs| *secs > 0)
+                .unwrap_or_else(OracleConfig::default_max_age_secs),
+        };
+        let mut priced = HashSet::new();
+        for feed in &oracle.feeds {
+            ensure!(
+                priced.insert(feed.base_token),
+                "duplicate price feed for base token {:?}",
+                feed.base_token
+            );
+        }
+
+        let base_url = |name: &str, default: fn() -> String| -> Result<String> {
+            match std::env::var(name)
+                .ok()
+                .map(|url| url.trim().trim_end_matches('/').to_string())
+                .filter(|url| !url.is_empty())
+            {
+                Some(url) => {
+                    url::Url::parse(&url).with_context(|| format!("invalid {name} {url}"))?;
+                    Ok(url)
+                }
+                None => Ok(default()),
+            }
+        };
+        let api = ApiConfig {
+            dexscreener_url: base_url("DEXSCREENER_API_URL", ApiConfig::default_dexscreener_url)?,
+            goplus_url: base_url("GOPLUS_API_URL", ApiConfig::default_goplus_url)?,
+            defillama_url: base_url("DEFILLAMA_API_URL", ApiConfig::default_defillama_url)?,
+        };
+
+        let mut host_rate_limits = HttpConfig::default_host_rate_limits();
+        for entry in std::env::var("HTTP_HOST_RATE_LIMITS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            let (host, rate) = entry
+                .split_once('=')
+                .and_then(|(host, rate)| Some((host.trim(), rate.trim().parse::<f64>().ok()?)))
+                .filter(|(host, rate)| !host.is_empty() && *rate > 0.0)
+                .ok_or_else(|| anyhow!("invalid HTTP_HOST_RATE_LIMITS entry {entry}, expected host=requests_per_sec"))?;
+            host_rate_limits.insert(host.to_string(), rate);
+        }
+        let http = HttpConfig {
+            rate_limit_per_sec: std::env::var("HTTP_RATE_LIMIT_PER_SEC")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .filter(|rate| *rate > 0.0)
+                .unwrap_or_else(HttpConfig::default_rate_limit_per_sec),
+            host_rate_limits,
+            burst: std::env::var("HTTP_RATE_BURST")
+                .ok()
+                .and_then(|v| v.parse::<u32>().ok())
+                .filter(|burst| *burst > 0)
+                .unwrap_or_else(HttpConfig::default_burst),
+            max_retries: std::env::var("HTTP_MAX_RETRIES")
+                .ok()
+                .and_then(|v| v.parse::<u32>().ok())
+                .unwrap_or_else(HttpConfig::default_max_retries),
+            retry_backoff_ms: std::env::var("HTTP_RETRY_BACKOFF_MS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or_else(HttpConfig::default_retry_backoff_ms),
+            security_cache_secs: std::env::var("SECURITY_CACHE_SECS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or_else(HttpConfig::default_security_cache_secs),
+            market_cache_secs: std::env::var("MARKET_CACHE_SECS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or_else(HttpConfig::default_market_cache_secs),
+            cache_file: std::env::var("HTTP_CACHE_FILE")
+                .ok()
+                .filter(|path| !path.trim().is_empty())
+                .map(PathBuf::from),
+        };
+
+        let signer = if let Some(url) = std::env::var("REMOTE_SIGNER_URL")
+            .ok()
+            .filter(|url| !url.trim().is_empty())
+        {
+            SignerConfig::Remote {
+                url,
+                addresses: std::env::var("REMOTE_SIGNER_ADDRESS")
+                    .unwrap_or_default()
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|address| !address.is_empty())
+                    .map(|address| {
+                        address
+                            .parse::<Address>()
+                            .with_context(|| format!("invalid REMOTE_SIGNER_ADDRESS {address}"))
+                    })
+                    .collect::<Result<Vec<_>>>()?,
+            }
+        } else if let Some(paths) = std::env::var("KEYSTORE_PATH")
+            .ok()
+            .filter(|paths| !paths.trim().is_empty())
+        {
+            SignerConfig::Keystore {
+                paths: paths
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|path| !path.is_empty())
+                    .map(PathBuf::from)
+                    .collect(),
+                password_file: std::env::var("KEYSTORE_PASSWORD_FILE")
+                    .ok()
+                    .filter(|path| !path.trim().is_empty())
+                    .map(PathBuf::from),
+            }
+        } else if std::env::var("TRADING_MNEMONIC").is_ok_and(|phrase| !phrase.trim().is_empty()) {
+            SignerConfig::Mnemonic {
+                count: std::env::var("WALLET_COUNT")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or_else(SignerConfig::default_wallet_count),
+            }
+        } else {
+            SignerConfig::PrivateKeyEnv
+        };
+
+        let strategy_profiles = match std::env::var("STRATEGIES_FILE")
+            .ok()
+            .filter(|path| !path.trim().is_empty())
+        {
+            Some(path) => {
+                let data = std::fs::read_to_string(&path)
+                    .with_context(|| format!("read strategies file {path}"))?;
+                serde_json::from_str(&data)
+                    .with_context(|| format!("parse strategies file {path}"))?
+            }
+            None => Vec::new(),
+        };
+
+        let config = Self {
             chain,
             rpc,
             strategy,
@@ -321,18 +1354,53 @@ impl BotConfig {
             metadata,
             alerting,
             monitoring,
-        })
+            history,
+            signer,
+            oracle,
+            api,
+            http,
+            strategy_profiles,
+        };
+        for strategy in config.strategies()? {
+            let ignored = strategy.ignored_momentum_windows();
+            if !ignored.is_empty() {
+                warn!(
+                    strategy = %strategy.id,
+                    ?ignored,
+                    horizon_minutes = strategy.momentum.horizon_minutes,
+                    "momentum windows longer than MOMENTUM_HORIZON_MINUTES are ignored"
+                );
+            }
+        }
+        Ok(config)
+    }
+
+    /// Strategies to trade: `strategy` alone, or one per profile with the
+    /// settings it names overriding `strategy`'s.
+    pub fn strategies(&self) -> Result<Vec<StrategyConfig>> {
+        if self.strategy_profiles.is_empty() {
+            return Ok(vec![self.strategy.clone()]);
+        }
+        let base = serde_json::to_value(&self.strategy)?;
+        let mut ids = HashSet::new();
+        self.strategy_profiles
+            .iter()
+            .map(|profile| {
+                let id = profile
+                    .get("id")
+                    .and_then(Value::as_str)
+                    .filter(|id| !id.trim().is_empty())
+                    .ok_or_else(|| anyhow!("strategy profile without an id"))?;
+                ensure!(ids.insert(id), "duplicate strategy id {id}");
+                let mut merged = base.clone();
+                merge_json(&mut merged, profile);
+                serde_json::from_value(merged).with_context(|| format!("strategy profile {id}"))
+            })
+            .collect()
     }
 
     pub fn position_size_wei(&self) -> Result<U256> {
-        let wei_per_eth = U256::exp10(18);
-        let scaled = wei_per_eth
-            .checked_mul(U256::from(
-                (self.strategy.position_size_eth * 1e6_f64) as u64,
-            ))
-            .ok_or_else(|| anyhow!("position size overflow"))?
-            / U256::from(1_000_000u64);
-        Ok(scaled)
+        eth_to_wei(self.strategy.position_size_eth)
     }
 
     pub fn slippage_bps(&self) -> u16 {
@@ -355,3 +1423,111 @@ impl BotConfig {
         }
     }
 }
+
+/// Builds the venue registry from `ROUTER_ADDRESS` (the primary V2 router),
+/// the optional `V3_*` variables, and any extra venues listed in the JSON file
+/// at `VENUES_FILE`.
+fn load_venues() -> Result<Vec<VenueConfig>> {
+    let dex_id = std::env::var("ROUTER_DEX_ID").unwrap_or_else(|_| "uniswap".to_string());
+    let mut venues = Vec::new();
+
+    if let Ok(router) = std::env::var("ROUTER_ADDRESS") {
+        venues.push(VenueConfig {
+            dex_id: dex_id.clone(),
+            protocol: VenueProtocol::UniswapV2,
+            router_address: Address::from_str(router.trim()).context("invalid ROUTER_ADDRESS")?,
+            factory_address: env_address("ROUTER_FACTORY_ADDRESS")?,
+            quoter_address: None,
+            fee_tiers: Vec::new(),
+        });
+    }
+
+    match (
+        std::env::var("V3_QUOTER_ADDRESS").ok(),
+        std::env::var("V3_ROUTER_ADDRESS").ok(),
+    ) {
+        (Some(quoter), Some(router)) => venues.push(VenueConfig {
+            dex_id,
+            protocol: VenueProtocol::UniswapV3,
+            router_address: Address::from_str(router.trim())
+                .context("invalid V3_ROUTER_ADDRESS")?,
+            factory_address: env_address("V3_FACTORY_ADDRESS")?,
+            quoter_address: Some(
+                Address::from_str(quoter.trim()).context("invalid V3_QUOTER_ADDRESS")?,
+            ),
+            fee_tiers: std::env::var("V3_FEE_TIERS")
+                .ok()
+                .map(|tiers| {
+                    tiers
+                        .split(',')
+                        .filter_map(|tier| tier.trim().parse::<u32>().ok())
+                        .collect::<Vec<_>>()
+                })
+                .filter(|tiers| !tiers.is_empty())
+                .unwrap_or_else(VenueConfig::default_fee_tiers),
+        }),
+        (None, None) => {}
+        _ => {
+            return Err(anyhow!(
+                "V3_QUOTER_ADDRESS and V3_ROUTER_ADDRESS must be set together"
+            ));
+        }
+    }
+
+    if let Some(path) = std::env::var("VENUES_FILE")
+        .ok()
+        .filter(|path| !path.trim().is_empty())
+    {
+        let data =
+            std::fs::read_to_string(&path).with_context(|| format!("read venues file {path}"))?;
+        let extra: Vec<VenueConfig> =
+            serde_json::from_str(&data).with_context(|| format!("parse venues file {path}"))?;
+        venues.extend(extra);
+    }
+
+    if venues.is_empty() {
+        return Err(anyhow!(
+            "no swap venues configured: set ROUTER_ADDRESS or VENUES_FILE"
+        ));
+    }
+    for venue in &venues {
+        venue.validate()?;
+    }
+    Ok(venues)
+}
+
+fn env_address(key: &str) -> Result<Option<Address>> {
+    std::env::var(key)
+        .ok()
+        .filter(|addr| !addr.trim().is_empty())
+        .map(|addr| Address::from_str(addr.trim()).with_context(|| format!("invalid {key}")))
+        .transpose()
+}
+
+/// Converts a native coin amount to wei, to six decimals.
+pub fn eth_to_wei(amount_eth: f64) -> Result<U256> {
+    let wei_per_eth = U256::exp10(18);
+    let scaled = wei_per_eth
+        .checked_mul(U256::from((amount_eth * 1e6_f64) as u64))
+        .ok_or_else(|| anyhow!("position size overflow"))?
+        / U256::from(1_000_000u64);
+    Ok(scaled)
+}
+
+/// Overwrites the fields of `base` named in `overrides`, descending into
+/// nested objects.
+fn merge_json(base: &mut Value, overrides: &Map<String, Value>) {
+    let Value::Object(base) = base else {
+        return;
+    };
+    for (key, value) in overrides {
+        match (base.get_mut(key), value) {
+            (Some(existing @ Value::Object(_)), Value::Object(nested)) => {
+                merge_json(existing, nested)
+            }
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
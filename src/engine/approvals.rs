@@ -0,0 +1,69 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{Context, Result};
+use ethers::types::{Address, TxHash, U256};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+const STORAGE_FILE: &str = "approvals_state.json";
+
+/// An ERC-20 allowance the bot granted to a router.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalRecord {
+    #[serde(default)]
+    pub owner: Address,
+    pub token: Address,
+    pub spender: Address,
+    pub amount: U256,
+    pub tx_hash: TxHash,
+    pub granted_at: OffsetDateTime,
+}
+
+/// JSON-backed record of outstanding approvals, used to revoke them once the
+/// tokens are no longer held.
+#[derive(Debug, Default)]
+pub struct ApprovalLedger {
+    records: Vec<ApprovalRecord>,
+    storage_path: PathBuf,
+}
+
+impl ApprovalLedger {
+    pub fn load() -> Result<Self> {
+        let path = PathBuf::from(STORAGE_FILE);
+        if !path.exists() {
+            return Ok(Self {
+                records: Vec::new(),
+                storage_path: path,
+            });
+        }
+
+        let data = fs::read_to_string(&path).context("read approvals file")?;
+        let records: Vec<ApprovalRecord> =
+            serde_json::from_str(&data).context("parse approvals json")?;
+
+        Ok(Self {
+            records,
+            storage_path: path,
+        })
+    }
+
+    pub fn persist(&self) -> Result<()> {
+        let json = serde_json::to_string_pretty(&self.records)?;
+        fs::write(&self.storage_path, json).context("write approvals file")
+    }
+
+    pub fn record(&mut self, record: ApprovalRecord) {
+        self.remove(record.owner, record.token, record.spender);
+        self.records.push(record);
+    }
+
+    pub fn remove(&mut self, owner: Address, token: Address, spender: Address) {
+        self.records.retain(|record| {
+            record.owner != owner || record.token != token || record.spender != spender
+        });
+    }
+
+    pub fn records(&self) -> Vec<ApprovalRecord> {
+        self.records.clone()
+    }
+}
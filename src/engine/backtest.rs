@@ -0,0 +1,516 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{Context, Result};
+use ethers::types::Address;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::debug;
+
+use crate::config::BotConfig;
+
+use super::{
+    dataset::MarketSnapshot,
+    history::Candle,
+    http::HttpClient,
+    risk::{GoPlusTokenSecurity, RiskAnalyzer},
+    scanner::{DexScreenerScanner, GemCandidate},
+    strategy::{MarketContext, PositionView, StrategyProfile},
+    trader::ExitReason,
+};
+
+/// Pool fee charged on every simulated swap.
+const SWAP_FEE_BPS: f64 = 30.0;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BacktestExit {
+    TakeProfit,
+    StopLoss,
+    RiskAlert,
+    Manual,
+    /// Still open when the dataset ended; closed at the last mark.
+    EndOfData,
+}
+
+impl From<ExitReason> for BacktestExit {
+    fn from(reason: ExitReason) -> Self {
+        match reason {
+            ExitReason::TakeProfit => Self::TakeProfit,
+            ExitReason::StopLoss => Self::StopLoss,
+            ExitReason::RiskAlert => Self::RiskAlert,
+            ExitReason::Manual => Self::Manual,
+        }
+    }
+}
+
+/// One simulated round trip (or the sold part of a position when the exit
+/// had to be downsized).
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestTrade {
+    pub strategy: String,
+    pub token: Address,
+    pub token_symbol: String,
+    pub pair_address: Address,
+    #[serde(with = "time::serde::rfc3339")]
+    pub entry_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub exit_at: OffsetDateTime,
+    pub entry_price_usd: f64,
+    pub exit_price_usd: f64,
+    pub cost_usd: f64,
+    pub proceeds_usd: f64,
+    pub pnl_usd: f64,
+    pub pnl_bps: f64,
+    pub risk_score: f64,
+    pub reason: BacktestExit,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestReport {
+    pub snapshots: usize,
+    pub trades: Vec<BacktestTrade>,
+    pub wins: usize,
+    pub win_rate: f64,
+    pub total_pnl_usd: f64,
+    pub return_pct: f64,
+    pub starting_equity_usd: f64,
+    pub final_equity_usd: f64,
+    pub max_drawdown_usd: f64,
+    pub max_drawdown_pct: f64,
+    /// Entries skipped because even a downsized order exceeded the price
+    /// impact limit.
+    pub skipped_for_impact: usize,
+    /// Realized PnL per strategy id.
+    pub pnl_by_strategy_usd: BTreeMap<String, f64>,
+}
+
+#[derive(Debug, Clone)]
+struct SimPosition {
+    strategy: String,
+    token: Address,
+    token_symbol: String,
+    pair_address: Address,
+    entry_at: OffsetDateTime,
+    tokens: f64,
+    cost_usd: f64,
+    risk_score: f64,
+    take_profit_bps: u32,
+    stop_loss_bps: u32,
+    price_usd: f64,
+    liquidity_usd: f64,
+}
+
+impl SimPosition {
+    fn value_usd(&self) -> f64 {
+        self.tokens * self.price_usd
+    }
+
+    fn view(&self) -> PositionView {
+        PositionView {
+            token: self.token,
+            entry_at: self.entry_at,
+            entry_value_usd: self.cost_usd,
+            value_usd: swap_output(self.value_usd(), self.liquidity_usd),
+            take_profit_bps: self.take_profit_bps,
+            stop_loss_bps: self.stop_loss_bps,
+        }
+    }
+}
+
+/// Replays recorded snapshots through the live candidate filter, risk policy
+/// and configured strategy. Swaps fill against a
+/// constant-product pool holding half of the pair's reported liquidity on
+/// each side, and exits are checked once per snapshot.
+pub struct Backtester {
+    config: BotConfig,
+    scanner: DexScreenerScanner,
+    risk: RiskAnalyzer,
+    strategies: Vec<StrategyProfile>,
+    starting_equity_usd: f64,
+}
+
+impl Backtester {
+    pub fn new(config: BotConfig, starting_equity_usd: f64) -> Result<Self> {
+        Ok(Self {
+            risk: RiskAnalyzer::new(config.clone(), HttpClient::default())?,
+            scanner: DexScreenerScanner::default(),
+            strategies: StrategyProfile::load_all(&config)?,
+            config,
+            starting_equity_usd,
+        })
+    }
+
+    pub fn run(&self, snapshots: &[MarketSnapshot]) -> Result<BacktestReport> {
+        let mut positions: Vec<SimPosition> = Vec::new();
+        let mut trades = Vec::new();
+        let mut realized_usd = 0.0;
+        let mut peak_equity = self.starting_equity_usd;
+        let mut max_drawdown_usd: f64 = 0.0;
+        let mut max_drawdown_pct: f64 = 0.0;
+        let mut skipped_for_impact = 0;
+        let mut last_at = None;
+        let mut candles: HashMap<Address, Vec<Candle>> = HashMap::new();
+
+        for snapshot in snapshots {
+            let now = snapshot.captured_at;
+            last_at = Some(now);
+            let pairs = self
+                .scanner
+                .candidates_from_pairs(&snapshot.pairs, &self.config)
+                .context("replay snapshot pairs")?;
+            let tracked = self
+                .scanner
+                .candidates_from_pairs(&snapshot.tracked_pairs, &self.config)
+                .context("replay tracked pairs")?;
+            let by_pair: HashMap<Address, &GemCandidate> = tracked
+                .iter()
+                .chain(&pairs)
+                .map(|candidate| (candidate.pair_address, candidate))
+                .collect();
+
+            for candidate in by_pair.values() {
+                let series = candles.entry(candidate.pair_address).or_default();
+                series.push(snapshot_candle(candidate, now));
+                if series.len() > self.config.history.max_candles {
+                    series.remove(0);
+                }
+            }
+
+            for position in &mut positions {
+                if let Some(candidate) = by_pair.get(&position.pair_address) {
+                    position.price_usd = candidate.price_usd;
+                    position.liquidity_usd = candidate.liquidity_usd;
+                }
+            }
+
+            let mut still_open = Vec::with_capacity(positions.len());
+            for mut position in positions {
+                let history = candles
+                    .get(&position.pair_address)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                match self.check_exit(&mut position, history, now) {
+                    Some(trade) => {
+                        realized_usd += trade.pnl_usd;
+                        trades.push(trade);
+                        if position.tokens > 0.0 {
+                            still_open.push(position);
+                        }
+                    }
+                    None => still_open.push(position),
+                }
+            }
+            positions = still_open;
+
+            if !self.all_full(&positions) {
+                let candidates = self
+                    .scanner
+                    .select_candidates(pairs.clone(), &self.config, now);
+                for candidate in candidates {
+                    if self.all_full(&positions) {
+                        break;
+                    }
+                    if positions
+                        .iter()
+                        .any(|position| position.token == candidate.token_address)
+                    {
+                        continue;
+                    }
+                    let history = candles
+                        .get(&candidate.pair_address)
+                        .map(Vec::as_slice)
+                        .unwrap_or_default();
+                    for profile in &self.strategies {
+                        let (open, exposure_usd) = exposure(&positions, profile.id());
+                        if profile.is_full(open) {
+                            continue;
+                        }
+                        match self.try_entry(
+                            profile,
+                            exposure_usd,
+                            &candidate,
+                            snapshot,
+                            history,
+                            now,
+                        )? {
+                            Entry::Filled(position) => {
+                                positions.push(position);
+                                break;
+                            }
+                            Entry::TooMuchImpact => skipped_for_impact += 1,
+                            Entry::Rejected => {}
+                        }
+                    }
+                }
+            }
+
+            let unrealized_usd: f64 = positions
+                .iter()
+                .map(|position| {
+                    swap_output(position.value_usd(), position.liquidity_usd) - position.cost_usd
+                })
+                .sum();
+            let equity = self.starting_equity_usd + realized_usd + unrealized_usd;
+            peak_equity = peak_equity.max(equity);
+            let drawdown = peak_equity - equity;
+            max_drawdown_usd = max_drawdown_usd.max(drawdown);
+            if peak_equity > 0.0 {
+                max_drawdown_pct = max_drawdown_pct.max(drawdown / peak_equity * 100.0);
+            }
+        }
+
+        if let Some(end) = last_at {
+            for mut position in positions {
+                let tokens = position.tokens;
+                let trade = close(&mut position, tokens, end, BacktestExit::EndOfData);
+                realized_usd += trade.pnl_usd;
+                trades.push(trade);
+            }
+        }
+
+        let mut pnl_by_strategy_usd = BTreeMap::new();
+        for trade in &trades {
+            *pnl_by_strategy_usd
+                .entry(trade.strategy.clone())
+                .or_default() += trade.pnl_usd;
+        }
+        let wins = trades.iter().filter(|trade| trade.pnl_usd > 0.0).count();
+        let final_equity_usd = self.starting_equity_usd + realized_usd;
+        Ok(BacktestReport {
+            snapshots: snapshots.len(),
+            win_rate: if trades.is_empty() {
+                0.0
+            } else {
+                wins as f64 / trades.len() as f64
+            },
+            wins,
+            trades,
+            total_pnl_usd: realized_usd,
+            return_pct: if self.starting_equity_usd > 0.0 {
+                realized_usd / self.starting_equity_usd * 100.0
+            } else {
+                0.0
+            },
+            starting_equity_usd: self.starting_equity_usd,
+            final_equity_usd,
+            max_drawdown_usd,
+            max_drawdown_pct,
+            skipped_for_impact,
+            pnl_by_strategy_usd,
+        })
+    }
+
+    fn all_full(&self, positions: &[SimPosition]) -> bool {
+        self.strategies
+            .iter()
+            .all(|profile| profile.is_full(exposure(positions, profile.id()).0))
+    }
+
+    /// The strategy that opened `position`.
+    fn strategy_for(&self, position: &SimPosition) -> &StrategyProfile {
+        self.strategies
+            .iter()
+            .find(|profile| profile.id() == position.strategy)
+            .unwrap_or(&self.strategies[0])
+    }
+
+    fn try_entry(
+        &self,
+        profile: &StrategyProfile,
+        exposure_usd: f64,
+        candidate: &GemCandidate,
+        snapshot: &MarketSnapshot,
+        history: &[Candle],
+        now: OffsetDateTime,
+    ) -> Result<Entry> {
+        let security = snapshot
+            .security
+            .get(&candidate.token_address)
+            .map(|raw| {
+                GoPlusTokenSecurity::deserialize(raw).context("decode recorded goplus report")
+            })
+            .transpose()?;
+        let report = self.risk.assess(candidate, security)?;
+        if !report.is_safe {
+            debug!(token = ?candidate.token_address, reason = ?report.flags, "rejected candidate due to risk");
+            return Ok(Entry::Rejected);
+        }
+        if candidate.price_usd <= 0.0 || candidate.usd_per_base <= 0.0 {
+            return Ok(Entry::Rejected);
+        }
+        let market = MarketContext {
+            now,
+            candles: history,
+        };
+        let Some(intent) = profile.strategy.should_enter(candidate, &report, &market) else {
+            return Ok(Entry::Rejected);
+        };
+        let cost_usd = intent.size_eth * candidate.usd_per_base;
+        if !profile.fits_budget(exposure_usd, cost_usd) {
+            return Ok(Entry::Rejected);
+        }
+
+        let Some(size_usd) = self.fit_impact(cost_usd, candidate.liquidity_usd) else {
+            return Ok(Entry::TooMuchImpact);
+        };
+
+        let tokens = swap_output(size_usd, candidate.liquidity_usd) / candidate.price_usd;
+        Ok(Entry::Filled(SimPosition {
+            strategy: profile.id().to_string(),
+            token: candidate.token_address,
+            token_symbol: candidate.token_symbol.clone(),
+            pair_address: candidate.pair_address,
+            entry_at: now,
+            tokens,
+            cost_usd: size_usd,
+            risk_score: report.score,
+            take_profit_bps: intent.take_profit_bps,
+            stop_loss_bps: intent.stop_loss_bps,
+            price_usd: candidate.price_usd,
+            liquidity_usd: candidate.liquidity_usd,
+        }))
+    }
+
+    /// Sells when the strategy exits the position, downsizing or holding
+    /// like the live trader when the sale would exceed the price impact
+    /// limit. Stop-losses and risk exits sell everything when no size fits.
+    fn check_exit(
+        &self,
+        position: &mut SimPosition,
+        history: &[Candle],
+        now: OffsetDateTime,
+    ) -> Option<BacktestTrade> {
+        let market = MarketContext {
+            now,
+            candles: history,
+        };
+        let intent = self
+            .strategy_for(position)
+            .strategy
+            .should_exit(&position.view(), &market)?;
+
+        let sell_usd = match self.fit_impact(position.value_usd(), position.liquidity_usd) {
+            Some(sell_usd) => sell_usd,
+            None if intent.reason.is_protective() => position.value_usd(),
+            None => return None,
+        };
+        let tokens = position.tokens * (sell_usd / position.value_usd()).min(1.0);
+        Some(close(position, tokens, now, intent.reason.into()))
+    }
+
+    /// Largest trade up to `size_usd` within the price impact limit, or
+    /// `None` when it would have to be skipped.
+    fn fit_impact(&self, size_usd: f64, liquidity_usd: f64) -> Option<f64> {
+        let limit = self.config.exchange.max_price_impact_bps as f64 / 10_000.0;
+        if price_impact(size_usd, liquidity_usd) <= limit {
+            return Some(size_usd);
+        }
+        if !self.config.exchange.downsize_on_price_impact || limit >= 1.0 {
+            return None;
+        }
+        // impact = size / (reserve + size)  =>  size = reserve * limit / (1 - limit)
+        let reserve = liquidity_usd / 2.0;
+        let fitted = reserve * limit / (1.0 - limit);
+        (fitted > 0.0).then_some(fitted)
+    }
+}
+
+enum Entry {
+    Filled(SimPosition),
+    TooMuchImpact,
+    Rejected,
+}
+
+/// Sells `tokens` of `position` at its current mark and books the trade,
+/// shrinking the position and its cost basis.
+fn close(
+    position: &mut SimPosition,
+    tokens: f64,
+    at: OffsetDateTime,
+    reason: BacktestExit,
+) -> BacktestTrade {
+    let fraction = if position.tokens > 0.0 {
+        (tokens / position.tokens).min(1.0)
+    } else {
+        1.0
+    };
+    let cost_usd = position.cost_usd * fraction;
+    let proceeds_usd = swap_output(tokens * position.price_usd, position.liquidity_usd);
+    let pnl_usd = proceeds_usd - cost_usd;
+
+    let trade = BacktestTrade {
+        strategy: position.strategy.clone(),
+        token: position.token,
+        token_symbol: position.token_symbol.clone(),
+        pair_address: position.pair_address,
+        entry_at: position.entry_at,
+        exit_at: at,
+        entry_price_usd: if position.tokens > 0.0 {
+            position.cost_usd / position.tokens
+        } else {
+            0.0
+        },
+        exit_price_usd: if tokens > 0.0 {
+            proceeds_usd / tokens
+        } else {
+            0.0
+        },
+        cost_usd,
+        proceeds_usd,
+        pnl_usd,
+        pnl_bps: if cost_usd > 0.0 {
+            (proceeds_usd / cost_usd - 1.0) * 10_000.0
+        } else {
+            0.0
+        },
+        risk_score: position.risk_score,
+        reason,
+    };
+
+    position.tokens -= tokens;
+    position.cost_usd -= cost_usd;
+    if position.tokens <= f64::EPSILON {
+        position.tokens = 0.0;
+    }
+    trade
+}
+
+/// Open positions of `strategy` and their total cost in USD.
+fn exposure(positions: &[SimPosition], strategy: &str) -> (usize, f64) {
+    positions
+        .iter()
+        .filter(|position| position.strategy == strategy)
+        .fold((0, 0.0), |(count, cost), position| {
+            (count + 1, cost + position.cost_usd)
+        })
+}
+
+/// Price impact of swapping `size_usd` into a pool with `liquidity_usd`
+/// split evenly across both reserves.
+fn price_impact(size_usd: f64, liquidity_usd: f64) -> f64 {
+    let reserve = liquidity_usd / 2.0;
+    if reserve <= 0.0 {
+        return 1.0;
+    }
+    size_usd / (reserve + size_usd)
+}
+
+/// Spot value received for swapping `size_usd` in, after impact and fee.
+fn swap_output(size_usd: f64, liquidity_usd: f64) -> f64 {
+    size_usd * (1.0 - price_impact(size_usd, liquidity_usd)) * (1.0 - SWAP_FEE_BPS / 10_000.0)
+}
+
+/// A snapshot is a single price observation, so its candle is flat. The
+/// pair's last-hour volume stands in for the candle's volume: it only weights
+/// VWAP, where the proportions between snapshots are what matter.
+fn snapshot_candle(candidate: &GemCandidate, at: OffsetDateTime) -> Candle {
+    Candle {
+        open_at: at,
+        open: candidate.price_usd,
+        high: candidate.price_usd,
+        low: candidate.price_usd,
+        close: candidate.price_usd,
+        volume_usd: candidate.volume_h1_usd,
+        samples: 1,
+    }
+}
@@ -0,0 +1,143 @@
+use std::{
+    collections::HashMap,
+    fs::{self, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use ethers::types::{Address, U256};
+use flate2::{Compression, read::MultiGzDecoder, write::GzEncoder};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use time::{Duration, OffsetDateTime};
+
+/// Market state captured at one instant: the raw DexScreener pair objects
+/// seen by discovery and the raw GoPlus `token_security` entry per token.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketSnapshot {
+    #[serde(with = "time::serde::rfc3339")]
+    pub captured_at: OffsetDateTime,
+    #[serde(default)]
+    pub pairs: Vec<Value>,
+    /// Raw DexScreener token responses for pairs that are tracked but were
+    /// not in this round's discovery listings. Used for marks only.
+    #[serde(default)]
+    pub tracked_pairs: Vec<Value>,
+    #[serde(default)]
+    pub security: HashMap<Address, Value>,
+    #[serde(default)]
+    pub quotes: Vec<RecordedQuote>,
+}
+
+/// Router quote for buying `token` with the configured position size.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedQuote {
+    pub pair_address: Address,
+    pub token: Address,
+    pub base_token: Address,
+    pub router: Address,
+    pub amount_in: U256,
+    pub amount_out: U256,
+}
+
+/// Appends snapshots to gzip-compressed JSONL files named after the time
+/// the file was opened, starting a new file every `rotate_after`.
+///
+/// Every snapshot is written as its own gzip member, so a file cut short by
+/// a crash still decodes up to the last complete snapshot.
+pub struct SnapshotWriter {
+    dir: PathBuf,
+    rotate_after: Duration,
+    current: Option<(PathBuf, OffsetDateTime)>,
+}
+
+impl SnapshotWriter {
+    pub fn new(dir: PathBuf, rotate_after: Duration) -> Result<Self> {
+        fs::create_dir_all(&dir).with_context(|| format!("create dataset directory {dir:?}"))?;
+        Ok(Self {
+            dir,
+            rotate_after,
+            current: None,
+        })
+    }
+
+    /// Writes `snapshot` and returns the file it went to.
+    pub fn append(&mut self, snapshot: &MarketSnapshot) -> Result<&Path> {
+        let rotate = self
+            .current
+            .as_ref()
+            .is_none_or(|(_, opened_at)| snapshot.captured_at - *opened_at >= self.rotate_after);
+        if rotate {
+            let at = snapshot.captured_at.to_offset(time::UtcOffset::UTC);
+            let stamp = format!(
+                "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+                at.year(),
+                u8::from(at.month()),
+                at.day(),
+                at.hour(),
+                at.minute(),
+                at.second()
+            );
+            let path = self.dir.join(format!("snapshots-{stamp}.jsonl.gz"));
+            self.current = Some((path, snapshot.captured_at));
+        }
+        let (path, _) = self.current.as_ref().expect("snapshot file selected");
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("open dataset file {path:?}"))?;
+        let mut encoder = GzEncoder::new(file, Compression::default());
+        serde_json::to_writer(&mut encoder, snapshot)?;
+        encoder.write_all(b"\n")?;
+        encoder
+            .finish()
+            .with_context(|| format!("write dataset file {path:?}"))?;
+        Ok(path)
+    }
+}
+
+/// Loads snapshots from a JSONL file, or from every `.jsonl` and
+/// `.jsonl.gz` file in a directory, ordered by capture time.
+pub fn load_snapshots(path: &Path) -> Result<Vec<MarketSnapshot>> {
+    let files = if path.is_dir() {
+        let mut files = fs::read_dir(path)
+            .with_context(|| format!("read dataset directory {path:?}"))?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<PathBuf>, _>>()?;
+        files.retain(|file| {
+            let name = file.to_string_lossy();
+            name.ends_with(".jsonl") || name.ends_with(".jsonl.gz")
+        });
+        files.sort();
+        files
+    } else {
+        vec![path.to_path_buf()]
+    };
+
+    let mut snapshots = Vec::new();
+    for file in files {
+        let opened =
+            fs::File::open(&file).with_context(|| format!("open dataset file {file:?}"))?;
+        let reader: Box<dyn BufRead> = if file.extension().is_some_and(|ext| ext == "gz") {
+            Box::new(BufReader::new(MultiGzDecoder::new(opened)))
+        } else {
+            Box::new(BufReader::new(opened))
+        };
+        for (index, line) in reader.lines().enumerate() {
+            let line = line.with_context(|| format!("read {file:?}"))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            snapshots.push(
+                serde_json::from_str::<MarketSnapshot>(&line)
+                    .with_context(|| format!("parse snapshot {file:?}:{}", index + 1))?,
+            );
+        }
+    }
+
+    snapshots.sort_by_key(|snapshot| snapshot.captured_at);
+    Ok(snapshots)
+}
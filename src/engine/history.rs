@@ -0,0 +1,239 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result};
+use ethers::types::Address;
+use serde::{Deserialize, Serialize};
+use time::{Duration, OffsetDateTime};
+
+use crate::config::HistoryConfig;
+
+use super::trader::Trader;
+
+const STORAGE_FILE: &str = "price_history.json";
+
+/// One OHLCV bar of a pair's USD price.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Candle {
+    #[serde(with = "time::serde::rfc3339")]
+    pub open_at: OffsetDateTime,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// USD value of the base token moved in or out of the pool between
+    /// samples. Swaps that cancel out within one sample interval are missed.
+    pub volume_usd: f64,
+    pub samples: u32,
+}
+
+/// A pair the sampler should read, with the decimals learned so far.
+#[derive(Debug, Clone)]
+pub struct SampleTarget {
+    pub pair: Address,
+    pub token: Address,
+    pub base_token: Address,
+    pub concentrated: bool,
+    token_decimals: Option<u8>,
+    base_decimals: Option<u8>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PriceSample {
+    pub at: OffsetDateTime,
+    pub price_usd: f64,
+    /// Pool balance of the base token, in whole tokens.
+    pub base_reserve: f64,
+    pub usd_per_base: f64,
+    token_decimals: u8,
+    base_decimals: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PairSeries {
+    token: Address,
+    base_token: Address,
+    concentrated: bool,
+    #[serde(default)]
+    token_decimals: Option<u8>,
+    #[serde(default)]
+    base_decimals: Option<u8>,
+    #[serde(with = "time::serde::rfc3339")]
+    tracked_until: OffsetDateTime,
+    #[serde(default)]
+    last_base_reserve: Option<f64>,
+    #[serde(default)]
+    candles: VecDeque<Candle>,
+}
+
+/// Rolling OHLCV candles per tracked pair, built from our own pool samples
+/// and persisted to disk between runs.
+#[derive(Debug)]
+pub struct PriceHistory {
+    series: HashMap<Address, PairSeries>,
+    candle: Duration,
+    max_candles: usize,
+    storage_path: PathBuf,
+}
+
+impl PriceHistory {
+    pub fn new(config: &HistoryConfig) -> Self {
+        Self {
+            series: HashMap::new(),
+            candle: Duration::seconds(config.candle_secs as i64),
+            max_candles: config.max_candles,
+            storage_path: PathBuf::from(STORAGE_FILE),
+        }
+    }
+
+    pub fn load(config: &HistoryConfig) -> Result<Self> {
+        let mut history = Self::new(config);
+        if history.storage_path.exists() {
+            let data =
+                fs::read_to_string(&history.storage_path).context("read price history file")?;
+            history.series = serde_json::from_str(&data).context("parse price history json")?;
+        }
+        Ok(history)
+    }
+
+    pub fn persist(&self) -> Result<()> {
+        let json = serde_json::to_string(&self.series)?;
+        fs::write(&self.storage_path, json).context("write price history file")
+    }
+
+    /// Samples `pair` at least until `until`, keeping any history it has.
+    pub fn track(
+        &mut self,
+        pair: Address,
+        token: Address,
+        base_token: Address,
+        concentrated: bool,
+        until: OffsetDateTime,
+    ) {
+        let series = self.series.entry(pair).or_insert_with(|| PairSeries {
+            token,
+            base_token,
+            concentrated,
+            token_decimals: None,
+            base_decimals: None,
+            tracked_until: until,
+            last_base_reserve: None,
+            candles: VecDeque::new(),
+        });
+        series.tracked_until = series.tracked_until.max(until);
+    }
+
+    /// Forgets pairs whose tracking ran out and returns the rest to sample.
+    pub fn targets(&mut self, now: OffsetDateTime) -> Vec<SampleTarget> {
+        self.series.retain(|_, series| series.tracked_until > now);
+        self.series
+            .iter()
+            .map(|(pair, series)| SampleTarget {
+                pair: *pair,
+                token: series.token,
+                base_token: series.base_token,
+                concentrated: series.concentrated,
+                token_decimals: series.token_decimals,
+                base_decimals: series.base_decimals,
+            })
+            .collect()
+    }
+
+    /// Folds a sample into the pair's current candle, opening a new one on
+    /// each candle boundary.
+    pub fn record(&mut self, pair: Address, sample: &PriceSample) {
+        let Some(series) = self.series.get_mut(&pair) else {
+            return;
+        };
+        series.token_decimals = Some(sample.token_decimals);
+        series.base_decimals = Some(sample.base_decimals);
+
+        let volume_usd = series
+            .last_base_reserve
+            .map(|last| (sample.base_reserve - last).abs() * sample.usd_per_base)
+            .unwrap_or(0.0);
+        series.last_base_reserve = Some(sample.base_reserve);
+
+        let candle_secs = self.candle.whole_seconds().max(1);
+        let open_at = OffsetDateTime::from_unix_timestamp(
+            sample.at.unix_timestamp() - sample.at.unix_timestamp().rem_euclid(candle_secs),
+        )
+        .unwrap_or(sample.at);
+
+        match series.candles.back_mut() {
+            Some(candle) if candle.open_at == open_at => {
+                candle.high = candle.high.max(sample.price_usd);
+                candle.low = candle.low.min(sample.price_usd);
+                candle.close = sample.price_usd;
+                candle.volume_usd += volume_usd;
+                candle.samples += 1;
+            }
+            _ => {
+                series.candles.push_back(Candle {
+                    open_at,
+                    open: sample.price_usd,
+                    high: sample.price_usd,
+                    low: sample.price_usd,
+                    close: sample.price_usd,
+                    volume_usd,
+                    samples: 1,
+                });
+                while series.candles.len() > self.max_candles {
+                    series.candles.pop_front();
+                }
+            }
+        }
+    }
+
+    /// Candles of `pair`, oldest first; empty when the pair is not tracked.
+    pub fn candles(&self, pair: &Address) -> Vec<Candle> {
+        self.series
+            .get(pair)
+            .map(|series| series.candles.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn tracked_pairs(&self) -> Vec<Address> {
+        self.series.keys().copied().collect()
+    }
+}
+
+/// Reads the pool behind `target` and prices it in USD.
+pub(super) async fn sample_pair(
+    trader: &Trader,
+    target: &SampleTarget,
+    usd_per_base: f64,
+) -> Result<PriceSample> {
+    let token_decimals = match target.token_decimals {
+        Some(decimals) => decimals,
+        None => trader.token_decimals(target.token).await?,
+    };
+    let base_decimals = match target.base_decimals {
+        Some(decimals) => decimals,
+        None => trader.token_decimals(target.base_token).await?,
+    };
+    let spot = trader
+        .pool_spot(
+            target.pair,
+            target.token,
+            target.base_token,
+            target.concentrated,
+        )
+        .await?;
+
+    let base_per_token =
+        spot.base_per_token * 10f64.powi(i32::from(token_decimals) - i32::from(base_decimals));
+    let base_reserve =
+        ethers::utils::format_units(spot.base_reserve, u32::from(base_decimals))?.parse::<f64>()?;
+    Ok(PriceSample {
+        at: OffsetDateTime::now_utc(),
+        price_usd: base_per_token * usd_per_base,
+        base_reserve,
+        usd_per_base,
+        token_decimals,
+        base_decimals,
+    })
+}
@@ -0,0 +1,247 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result, anyhow};
+use reqwest::{Client, StatusCode, header::RETRY_AFTER};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use time::OffsetDateTime;
+use tokio::time::sleep;
+use tracing::{debug, warn};
+
+use crate::config::HttpConfig;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Longest `Retry-After` honoured before falling back to our own backoff.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// HTTP client shared by the market data and security integrations: a
+/// token bucket per host, retries with backoff on 429 and 5xx responses,
+/// and a TTL cache of decoded responses, optionally kept on disk.
+#[derive(Clone)]
+pub struct HttpClient {
+    client: Client,
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    config: HttpConfig,
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+    cache: Mutex<HashMap<String, CachedResponse>>,
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    rate: f64,
+    burst: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    /// Takes a token, or returns how long until one is available.
+    fn take(&mut self, now: Instant) -> Option<Duration> {
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.burst);
+        self.refilled_at = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64((1.0 - self.tokens) / self.rate))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedResponse {
+    body: Value,
+    #[serde(with = "time::serde::rfc3339")]
+    expires_at: OffsetDateTime,
+}
+
+impl HttpClient {
+    /// Loads the persisted cache when `cache_file` is set; an unreadable
+    /// cache is discarded.
+    pub fn new(config: &HttpConfig) -> Self {
+        let cache = config
+            .cache_file
+            .as_ref()
+            .filter(|path| path.exists())
+            .map(|path| {
+                load_cache(path).unwrap_or_else(|err| {
+                    warn!(error = ?err, ?path, "http cache unreadable, starting empty");
+                    HashMap::new()
+                })
+            })
+            .unwrap_or_default();
+
+        Self {
+            client: Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .user_agent("agentic-memecoin-bot/1.0")
+                .build()
+                .expect("reqwest client build"),
+            inner: Arc::new(Inner {
+                config: config.clone(),
+                buckets: Mutex::new(HashMap::new()),
+                cache: Mutex::new(cache),
+            }),
+        }
+    }
+
+    pub fn security_ttl(&self) -> Duration {
+        Duration::from_secs(self.inner.config.security_cache_secs)
+    }
+
+    pub fn market_ttl(&self) -> Duration {
+        Duration::from_secs(self.inner.config.market_cache_secs)
+    }
+
+    /// GETs `url` as JSON, rate limited per host and retried on 429, 5xx
+    /// and transport errors.
+    pub async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T> {
+        let host = reqwest::Url::parse(url)
+            .with_context(|| format!("invalid url {url}"))?
+            .host_str()
+            .unwrap_or_default()
+            .to_string();
+        let backoff = Duration::from_millis(self.inner.config.retry_backoff_ms);
+
+        let mut attempt = 0;
+        loop {
+            self.throttle(&host).await;
+            let (err, retry_after) = match self.client.get(url).send().await {
+                Ok(resp) if resp.status().is_success() => {
+                    return resp
+                        .json()
+                        .await
+                        .with_context(|| format!("decode response from {host}"));
+                }
+                Ok(resp) if is_retryable(resp.status()) => {
+                    let retry_after = resp
+                        .headers()
+                        .get(RETRY_AFTER)
+                        .and_then(|value| value.to_str().ok())
+                        .and_then(|value| value.trim().parse::<u64>().ok())
+                        .map(Duration::from_secs)
+                        .filter(|wait| *wait <= MAX_RETRY_AFTER);
+                    (anyhow!("{host} returned {}", resp.status()), retry_after)
+                }
+                Ok(resp) => return Err(anyhow!("{host} returned {}", resp.status())),
+                Err(err) if err.is_timeout() || err.is_connect() || err.is_request() => {
+                    (anyhow::Error::new(err), None)
+                }
+                Err(err) => return Err(err.into()),
+            };
+
+            if attempt >= self.inner.config.max_retries {
+                return Err(err.context(format!("giving up on {host} after {attempt} retries")));
+            }
+            let wait = retry_after.unwrap_or(backoff * 2u32.pow(attempt));
+            debug!(
+                %host,
+                error = %err,
+                backoff_ms = wait.as_millis() as u64,
+                "http request failed, retrying"
+            );
+            sleep(wait).await;
+            attempt += 1;
+        }
+    }
+
+    /// The unexpired value cached under `key`.
+    pub fn cached<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let cache = self.inner.cache.lock().expect("http cache poisoned");
+        let entry = cache.get(key)?;
+        if entry.expires_at <= OffsetDateTime::now_utc() {
+            return None;
+        }
+        serde_json::from_value(entry.body.clone()).ok()
+    }
+
+    /// Caches `value` under `key` for `ttl`; a zero `ttl` caches nothing.
+    pub fn store<T: Serialize>(&self, key: &str, value: &T, ttl: Duration) {
+        if ttl.is_zero() {
+            return;
+        }
+        let Ok(body) = serde_json::to_value(value) else {
+            return;
+        };
+        let now = OffsetDateTime::now_utc();
+        let mut cache = self.inner.cache.lock().expect("http cache poisoned");
+        cache.retain(|_, entry| entry.expires_at > now);
+        cache.insert(
+            key.to_string(),
+            CachedResponse {
+                body,
+                expires_at: now + ttl,
+            },
+        );
+
+        let Some(path) = &self.inner.config.cache_file else {
+            return;
+        };
+        if let Err(err) = persist_cache(path, &cache) {
+            warn!(error = ?err, ?path, "failed to persist http cache");
+        }
+    }
+
+    async fn throttle(&self, host: &str) {
+        loop {
+            let wait = {
+                let mut buckets = self.inner.buckets.lock().expect("rate limiter poisoned");
+                let config = &self.inner.config;
+                buckets
+                    .entry(host.to_string())
+                    .or_insert_with(|| {
+                        let burst = f64::from(config.burst.max(1));
+                        TokenBucket {
+                            tokens: burst,
+                            rate: config
+                                .host_rate_limits
+                                .get(host)
+                                .copied()
+                                .unwrap_or(config.rate_limit_per_sec),
+                            burst,
+                            refilled_at: Instant::now(),
+                        }
+                    })
+                    .take(Instant::now())
+            };
+            match wait {
+                Some(wait) => sleep(wait).await,
+                None => return,
+            }
+        }
+    }
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new(&HttpConfig::default())
+    }
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+fn load_cache(path: &Path) -> Result<HashMap<String, CachedResponse>> {
+    let data = fs::read_to_string(path).context("read http cache file")?;
+    let mut cache: HashMap<String, CachedResponse> =
+        serde_json::from_str(&data).context("parse http cache json")?;
+    let now = OffsetDateTime::now_utc();
+    cache.retain(|_, entry| entry.expires_at > now);
+    Ok(cache)
+}
+
+fn persist_cache(path: &Path, cache: &HashMap<String, CachedResponse>) -> Result<()> {
+    let json = serde_json::to_string(cache)?;
+    fs::write(path, json).context("write http cache file")
+}
@@ -0,0 +1,368 @@
+use serde::Serialize;
+
+use crate::config::IndicatorConfig;
+
+use super::history::Candle;
+
+/// Exponential moving average of `values`, seeded with the simple average of
+/// the first `period` values.
+pub fn ema(values: &[f64], period: usize) -> Option<f64> {
+    if period == 0 || values.len() < period {
+        return None;
+    }
+    let alpha = 2.0 / (period as f64 + 1.0);
+    let seed = values[..period].iter().sum::<f64>() / period as f64;
+    Some(
+        values[period..]
+            .iter()
+            .fold(seed, |ema, value| ema + alpha * (value - ema)),
+    )
+}
+
+/// Wilder's relative strength index over closes, 0–100.
+pub fn rsi(closes: &[f64], period: usize) -> Option<f64> {
+    if period == 0 || closes.len() <= period {
+        return None;
+    }
+    let changes = closes
+        .windows(2)
+        .map(|pair| pair[1] - pair[0])
+        .collect::<Vec<_>>();
+    let (mut gain, mut loss) = changes[..period]
+        .iter()
+        .fold((0.0, 0.0), |(gain, loss), change| {
+            (gain + change.max(0.0), loss + (-change).max(0.0))
+        });
+    gain /= period as f64;
+    loss /= period as f64;
+    for change in &changes[period..] {
+        gain = (gain * (period as f64 - 1.0) + change.max(0.0)) / period as f64;
+        loss = (loss * (period as f64 - 1.0) + (-change).max(0.0)) / period as f64;
+    }
+    if loss == 0.0 {
+        return Some(if gain == 0.0 { 50.0 } else { 100.0 });
+    }
+    Some(100.0 - 100.0 / (1.0 + gain / loss))
+}
+
+/// Wilder's average true range, in USD.
+pub fn atr(candles: &[Candle], period: usize) -> Option<f64> {
+    if period == 0 || candles.len() <= period {
+        return None;
+    }
+    let ranges = candles
+        .windows(2)
+        .map(|pair| {
+            let (previous, candle) = (pair[0], pair[1]);
+            (candle.high - candle.low)
+                .max((candle.high - previous.close).abs())
+                .max((candle.low - previous.close).abs())
+        })
+        .collect::<Vec<_>>();
+    let seed = ranges[..period].iter().sum::<f64>() / period as f64;
+    Some(ranges[period..].iter().fold(seed, |atr, range| {
+        (atr * (period as f64 - 1.0) + range) / period as f64
+    }))
+}
+
+/// Volume-weighted average of the candles' typical price; `None` without
+/// volume.
+pub fn vwap(candles: &[Candle]) -> Option<f64> {
+    let (weighted, volume) = candles
+        .iter()
+        .fold((0.0, 0.0), |(weighted, volume), candle| {
+            let typical = (candle.high + candle.low + candle.close) / 3.0;
+            (
+                weighted + typical * candle.volume_usd,
+                volume + candle.volume_usd,
+            )
+        });
+    (volume > 0.0).then(|| weighted / volume)
+}
+
+/// Indicator values at the latest candle. Each is `None` until there are
+/// enough candles for its period.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct Indicators {
+    pub close: Option<f64>,
+    pub rsi: Option<f64>,
+    pub ema_short: Option<f64>,
+    pub ema_long: Option<f64>,
+    pub atr: Option<f64>,
+    pub vwap: Option<f64>,
+}
+
+impl Indicators {
+    pub fn compute(candles: &[Candle], config: &IndicatorConfig) -> Self {
+        let closes = candles
+            .iter()
+            .map(|candle| candle.close)
+            .collect::<Vec<_>>();
+        Self {
+            close: closes.last().copied(),
+            rsi: rsi(&closes, config.rsi_period),
+            ema_short: ema(&closes, config.ema_short_period),
+            ema_long: ema(&closes, config.ema_long_period),
+            atr: atr(candles, config.atr_period),
+            vwap: vwap(candles),
+        }
+    }
+
+    /// Why the entry filters reject this pair, or `None` when they pass.
+    /// A filter without enough history to evaluate rejects.
+    pub fn entry_rejection(&self, config: &IndicatorConfig) -> Option<&'static str> {
+        if config.min_rsi.is_some() || config.max_rsi.is_some() {
+            let Some(rsi) = self.rsi else {
+                return Some("not enough history for rsi");
+            };
+            if config.min_rsi.is_some_and(|min| rsi < min) {
+                return Some("rsi below minimum");
+            }
+            if config.max_rsi.is_some_and(|max| rsi > max) {
+                return Some("rsi above maximum");
+            }
+        }
+        if config.require_ema_trend {
+            match (self.ema_short, self.ema_long) {
+                (Some(short), Some(long)) if short > long => {}
+                (Some(_), Some(_)) => return Some("short ema not above long ema"),
+                _ => return Some("not enough history for ema trend"),
+            }
+        }
+        if config.require_above_ema {
+            match (self.close, self.ema_short) {
+                (Some(close), Some(short)) if close > short => {}
+                (Some(_), Some(_)) => return Some("price not above short ema"),
+                _ => return Some("not enough history for ema"),
+            }
+        }
+        if let Some(max_deviation) = config.max_vwap_deviation_pct {
+            let Some(deviation) = self.vwap_deviation_pct() else {
+                return Some("not enough history for vwap");
+            };
+            if deviation > max_deviation {
+                return Some("price too far above vwap");
+            }
+        }
+        None
+    }
+
+    /// Close relative to VWAP, in percent.
+    pub fn vwap_deviation_pct(&self) -> Option<f64> {
+        match (self.close, self.vwap) {
+            (Some(close), Some(vwap)) if vwap > 0.0 => Some((close / vwap - 1.0) * 100.0),
+            _ => None,
+        }
+    }
+
+    /// Stop distance of `atr_stop_multiplier` ATRs below the close, in basis
+    /// points and at least `atr_stop_min_bps`; `None` when ATR stops are off
+    /// or there is not enough history.
+    pub fn atr_stop_bps(&self, config: &IndicatorConfig) -> Option<u32> {
+        if config.atr_stop_multiplier <= 0.0 {
+            return None;
+        }
+        let (atr, close) = (self.atr?, self.close?);
+        if close <= 0.0 {
+            return None;
+        }
+        let bps = (atr * config.atr_stop_multiplier / close * 10_000.0).min(9_999.0);
+        Some((bps as u32).max(config.atr_stop_min_bps))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::OffsetDateTime;
+
+    use super::*;
+
+    const CLOSES: [f64; 5] = [10.0, 11.0, 12.0, 11.0, 13.0];
+
+    /// Candles one dollar either side of each close, so every typical price
+    /// equals the close. The last candle trades twice the volume.
+    fn candles(closes: &[f64], volume_usd: f64) -> Vec<Candle> {
+        let last = closes.len().saturating_sub(1);
+        closes
+            .iter()
+            .enumerate()
+            .map(|(index, &close)| Candle {
+                open_at: OffsetDateTime::UNIX_EPOCH,
+                open: close,
+                high: close + 1.0,
+                low: close - 1.0,
+                close,
+                volume_usd: if index == last {
+                    volume_usd * 2.0
+                } else {
+                    volume_usd
+                },
+                samples: 1,
+            })
+            .collect()
+    }
+
+    fn config() -> IndicatorConfig {
+        IndicatorConfig {
+            rsi_period: 3,
+            ema_short_period: 2,
+            ema_long_period: 3,
+            atr_period: 3,
+            ..IndicatorConfig::default()
+        }
+    }
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.expect("indicator should have a value");
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn ema_seeds_with_the_simple_average() {
+        // Seed (10 + 11 + 12) / 3 = 11, then alpha 0.5: 11 -> 11 -> 12.
+        assert_close(ema(&CLOSES, 3), 12.0);
+        assert_close(ema(&CLOSES, 5), 11.4);
+        assert_eq!(ema(&CLOSES, 6), None);
+        assert_eq!(ema(&CLOSES, 0), None);
+    }
+
+    #[test]
+    fn rsi_smooths_gains_and_losses() {
+        // Changes +1 +1 -1 +2: seed gain 2/3 and loss 1/3, then gain 10/9
+        // and loss 2/9, so RS = 5.
+        assert_close(rsi(&CLOSES, 3), 100.0 - 100.0 / 6.0);
+        assert_eq!(rsi(&CLOSES, 5), None);
+    }
+
+    #[test]
+    fn rsi_without_losses() {
+        assert_close(rsi(&[5.0; 6], 3), 50.0);
+        assert_close(rsi(&[1.0, 2.0, 3.0, 4.0], 3), 100.0);
+    }
+
+    #[test]
+    fn atr_uses_the_true_range() {
+        // True ranges 2, 2, 2 and 3 (the gap from 11 up to a 14 high).
+        let candles = candles(&CLOSES, 100.0);
+        assert_close(atr(&candles, 3), 7.0 / 3.0);
+        assert_eq!(atr(&candles, 5), None);
+    }
+
+    #[test]
+    fn vwap_weights_typical_prices_by_volume() {
+        // (10 + 11 + 12 + 11) * 100 + 13 * 200 over 600.
+        assert_close(vwap(&candles(&CLOSES, 100.0)), 7000.0 / 600.0);
+        assert_eq!(vwap(&candles(&CLOSES, 0.0)), None);
+        assert_eq!(vwap(&[]), None);
+    }
+
+    #[test]
+    fn compute_leaves_short_periods_empty() {
+        let indicators = Indicators::compute(&candles(&CLOSES[..3], 100.0), &config());
+        assert_close(indicators.close, 12.0);
+        assert_eq!(indicators.rsi, None);
+        assert_eq!(indicators.atr, None);
+        assert_close(indicators.ema_long, 11.0);
+    }
+
+    #[test]
+    fn entry_rejection_checks_each_filter() {
+        let indicators = Indicators::compute(&candles(&CLOSES, 100.0), &config());
+        let reject = |config: IndicatorConfig| indicators.entry_rejection(&config);
+
+        assert_eq!(reject(config()), None);
+        assert_eq!(
+            reject(IndicatorConfig {
+                min_rsi: Some(90.0),
+                ..config()
+            }),
+            Some("rsi below minimum")
+        );
+        assert_eq!(
+            reject(IndicatorConfig {
+                max_rsi: Some(80.0),
+                ..config()
+            }),
+            Some("rsi above maximum")
+        );
+        // Short EMA ends at 12.39 over a long EMA of 12, below the 13 close.
+        assert_eq!(
+            reject(IndicatorConfig {
+                require_ema_trend: true,
+                require_above_ema: true,
+                ..config()
+            }),
+            None
+        );
+        // 13 is 11.4% above the 11.67 VWAP.
+        assert_eq!(
+            reject(IndicatorConfig {
+                max_vwap_deviation_pct: Some(10.0),
+                ..config()
+            }),
+            Some("price too far above vwap")
+        );
+        assert_eq!(
+            reject(IndicatorConfig {
+                max_vwap_deviation_pct: Some(12.0),
+                ..config()
+            }),
+            None
+        );
+
+        let falling = Indicators::compute(&candles(&[13.0, 12.0, 11.0, 10.0], 100.0), &config());
+        assert_eq!(
+            falling.entry_rejection(&IndicatorConfig {
+                require_ema_trend: true,
+                ..config()
+            }),
+            Some("short ema not above long ema")
+        );
+        assert_eq!(
+            falling.entry_rejection(&IndicatorConfig {
+                require_above_ema: true,
+                ..config()
+            }),
+            Some("price not above short ema")
+        );
+
+        let short = Indicators::compute(&candles(&CLOSES[..3], 100.0), &config());
+        assert_eq!(
+            short.entry_rejection(&IndicatorConfig {
+                min_rsi: Some(30.0),
+                ..config()
+            }),
+            Some("not enough history for rsi")
+        );
+    }
+
+    #[test]
+    fn atr_stop_scales_with_the_multiplier() {
+        let indicators = Indicators::compute(&candles(&CLOSES, 100.0), &config());
+        let stop = |atr_stop_multiplier, atr_stop_min_bps| {
+            indicators.atr_stop_bps(&IndicatorConfig {
+                atr_stop_multiplier,
+                atr_stop_min_bps,
+                ..config()
+            })
+        };
+
+        // 2 * 7/3 / 13 = 35.9%.
+        assert_eq!(stop(2.0, 300), Some(3589));
+        assert_eq!(stop(2.0, 5_000), Some(5_000));
+        assert_eq!(stop(10.0, 300), Some(9_999));
+        assert_eq!(stop(0.0, 300), None);
+
+        let short = Indicators::compute(&candles(&CLOSES[..3], 100.0), &config());
+        assert_eq!(
+            short.atr_stop_bps(&IndicatorConfig {
+                atr_stop_multiplier: 2.0,
+                ..config()
+            }),
+            None
+        );
+    }
+}
@@ -1,67 +1,479 @@
+mod approvals;
+pub mod backtest;
+pub mod dataset;
+pub mod history;
+pub mod http;
+pub mod indicators;
+pub mod optimize;
+mod oracle;
+pub mod policy;
 pub mod portfolio;
+pub mod recorder;
+mod relay;
 pub mod risk;
+pub mod rpc;
 pub mod scanner;
+mod signer;
+pub mod strategy;
 mod trader;
+mod venue;
 
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use ethers::prelude::*;
-use tokio::{sync::RwLock, time::sleep};
-use tracing::{error, info, instrument};
+use tokio::{
+    sync::{RwLock, mpsc, watch},
+    task::JoinSet,
+    time::{MissedTickBehavior, interval, sleep, timeout},
+};
+use tracing::{debug, error, info, instrument, warn};
 
-use crate::config::BotConfig;
+use crate::config::{self, BotConfig};
 
 use self::{
-    portfolio::{Portfolio, PortfolioSnapshot, Position},
+    history::{Candle, PriceHistory},
+    http::HttpClient,
+    portfolio::{Portfolio, PortfolioSnapshot, Position, StrategySnapshot, WalletSnapshot},
     risk::{RiskAnalyzer, TokenRiskReport},
+    rpc::EndpointStatus,
     scanner::{DexScreenerScanner, GemCandidate},
-    trader::Trader,
+    strategy::{EntryIntent, MarketContext, StrategyProfile},
+    trader::{ExitOrder, ExitReason, RevokedApproval, Trader},
+    venue::SwapRoute,
 };
 
-const LOOP_INTERVAL: Duration = Duration::from_secs(30);
+const WS_RECONNECT_DELAY: Duration = Duration::from_secs(5);
+const FORCED_EXIT_QUEUE: usize = 32;
+/// Added to the swap deadline when waiting for in-flight trades at shutdown.
+const SHUTDOWN_MARGIN: Duration = Duration::from_secs(60);
+
+/// Exit requested outside the per-block target checks.
+#[derive(Debug)]
+struct ForcedExit {
+    position_id: uuid::Uuid,
+    reason: ExitReason,
+}
 
 pub struct TradingBot {
     config: BotConfig,
     trader: Trader,
     scanner: DexScreenerScanner,
     risk: RiskAnalyzer,
+    /// Strategies trading side by side; never empty.
+    strategies: Vec<StrategyProfile>,
     portfolio: Arc<RwLock<Portfolio>>,
+    history: Arc<RwLock<PriceHistory>>,
+    shutdown: watch::Sender<bool>,
 }
 
 impl TradingBot {
     pub async fn new(config: BotConfig) -> Result<Self> {
-        let trader = Trader::new(config.clone()).await?;
-        let scanner = DexScreenerScanner::default();
-        let risk = RiskAnalyzer::new(config.clone());
-        let portfolio = Arc::new(RwLock::new(Portfolio::load().unwrap_or_default()));
+        let http = HttpClient::new(&config.http);
+        let trader = Trader::new(config.clone(), http.clone()).await?;
+        let scanner = DexScreenerScanner::new(http.clone());
+        let risk = RiskAnalyzer::new(config.clone(), http)?;
+        let strategies = StrategyProfile::load_all(&config)?;
+        for profile in &strategies {
+            info!(
+                strategy = profile.id(),
+                rules = profile.strategy.name(),
+                max_positions = profile.config.max_positions,
+                budget_usd = ?profile.config.budget_usd,
+                "strategy loaded"
+            );
+        }
+        let mut portfolio = Portfolio::load().unwrap_or_default();
+        portfolio.assign_untagged(strategies[0].id());
+        let history = PriceHistory::load(&config.history).unwrap_or_else(|err| {
+            warn!(error = ?err, "price history unreadable, starting empty");
+            PriceHistory::new(&config.history)
+        });
 
         Ok(Self {
             config,
             trader,
             scanner,
             risk,
-            portfolio,
+            strategies,
+            portfolio: Arc::new(RwLock::new(portfolio)),
+            history: Arc::new(RwLock::new(history)),
+            shutdown: watch::channel(false).0,
         })
     }
 
+    /// Runs discovery, block watching, exit handling and risk monitoring as
+    /// separate tasks. Each task only takes the portfolio lock for short
+    /// state updates, so a slow entry never delays a stop-loss.
+    ///
+    /// After [`TradingBot::shutdown`] no new entries are started; the trades
+    /// already in flight get until the swap deadline plus a margin to
+    /// confirm, open positions are optionally sold, and the portfolio is
+    /// persisted before returning.
     #[instrument(skip(self), fields(chain = %self.config.chain))]
-    pub async fn run(&self) -> Result<()> {
+    pub async fn run(self: Arc<Self>, flatten_on_exit: bool) -> Result<()> {
         info!("starting automated trading loop");
+        let (heads_tx, heads_rx) = watch::channel(U64::zero());
+        let (forced_tx, forced_rx) = mpsc::channel(FORCED_EXIT_QUEUE);
+
+        let mut tasks = JoinSet::new();
+        let bot = self.clone();
+        tasks.spawn(async move { bot.discovery_loop().await });
+        let bot = self.clone();
+        tasks.spawn(async move { bot.watch_blocks(heads_tx).await });
+        let bot = self.clone();
+        tasks.spawn(async move { bot.exit_loop(heads_rx, forced_rx).await });
+        let bot = self.clone();
+        tasks.spawn(async move { bot.risk_loop(forced_tx).await });
+        let bot = self.clone();
+        tasks.spawn(async move { bot.history_loop().await });
+
+        tokio::select! {
+            // Some loops return as soon as shutdown starts; checking shutdown
+            // first hands them to the drain below instead of aborting the
+            // trades still in flight.
+            biased;
+            _ = self.wait_for_shutdown() => {}
+            // Otherwise the loops only return early when they cannot
+            // continue; take the rest down with the first one.
+            Some(joined) = tasks.join_next() => {
+                tasks.abort_all();
+                self.portfolio.read().await.persist()?;
+                self.history.read().await.persist()?;
+                return joined.context("trading task panicked")?;
+            }
+        }
+
+        info!("shutting down, draining in-flight trades");
+        let grace = self.config.swap_deadline() + SHUTDOWN_MARGIN;
+        let drained = timeout(grace, async {
+            while let Some(joined) = tasks.join_next().await {
+                match joined {
+                    Ok(Err(err)) => error!(error = ?err, "trading task failed during shutdown"),
+                    Err(err) => error!(error = ?err, "trading task panicked during shutdown"),
+                    Ok(Ok(())) => {}
+                }
+            }
+        })
+        .await;
+        if drained.is_err() {
+            warn!(
+                grace_secs = grace.as_secs(),
+                "in-flight trades still pending after grace period, abandoning"
+            );
+            tasks.abort_all();
+        }
+
+        if flatten_on_exit {
+            self.flatten_positions().await;
+        }
+        self.portfolio.read().await.persist()?;
+        self.history.read().await.persist()?;
+        info!("trading loop stopped");
+        Ok(())
+    }
+
+    /// Stops new entries and asks the trading loops to wind down.
+    pub fn shutdown(&self) {
+        self.shutdown.send_replace(true);
+    }
+
+    /// Resolves once [`TradingBot::shutdown`] has been called.
+    pub async fn wait_for_shutdown(&self) {
+        let mut shutdown = self.shutdown.subscribe();
+        // The sender lives as long as `self`, so this only returns on shutdown.
+        let _ = shutdown.wait_for(|stopping| *stopping).await;
+    }
+
+    fn is_shutting_down(&self) -> bool {
+        *self.shutdown.borrow()
+    }
+
+    async fn discovery_loop(&self) -> Result<()> {
+        let mut ticker = interval(Duration::from_secs(
+            self.config.strategy.discovery_interval_secs,
+        ));
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {
+                    if let Err(err) = self.discover().await {
+                        error!(error = ?err, "discovery failed");
+                    }
+                }
+                _ = self.wait_for_shutdown() => return Ok(()),
+            }
+        }
+    }
+
+    /// Publishes the latest block number, from the WebSocket new-heads
+    /// subscription when `RPC_WS` is set and by polling otherwise.
+    async fn watch_blocks(&self, heads: watch::Sender<U64>) -> Result<()> {
+        let Some(ws_url) = self.config.rpc.ws_url.clone() else {
+            tokio::select! {
+                result = self.poll_blocks(&heads) => return result,
+                _ = self.wait_for_shutdown() => return Ok(()),
+            }
+        };
+        loop {
+            tokio::select! {
+                result = self.follow_new_heads(&ws_url, &heads) => {
+                    if let Err(err) = result {
+                        warn!(error = ?err, "new head subscription lost, reconnecting");
+                    }
+                }
+                _ = self.wait_for_shutdown() => return Ok(()),
+            }
+            sleep(WS_RECONNECT_DELAY).await;
+        }
+    }
+
+    async fn follow_new_heads(&self, ws_url: &str, heads: &watch::Sender<U64>) -> Result<()> {
+        let ws = Provider::<Ws>::connect(ws_url)
+            .await
+            .context("connecting websocket provider")?;
+        let mut stream = ws
+            .subscribe_blocks()
+            .await
+            .context("subscribing to new heads")?;
+        info!("following new heads for exit evaluation");
+
+        while let Some(block) = stream.next().await {
+            if let Some(number) = block.number {
+                heads.send_replace(number);
+            }
+        }
+        bail!("new head stream ended")
+    }
+
+    async fn poll_blocks(&self, heads: &watch::Sender<U64>) -> Result<()> {
+        let provider = self.trader.provider();
+        let mut ticker = interval(Duration::from_millis(self.config.rpc.poll_interval_ms));
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match provider.get_block_number().await {
+                Ok(number) => {
+                    heads.send_if_modified(|latest| {
+                        let newer = number > *latest;
+                        if newer {
+                            *latest = number;
+                        }
+                        newer
+                    });
+                }
+                Err(err) => warn!(error = ?err, "polling block number failed"),
+            }
+        }
+    }
+
+    /// Owns every exit: checks targets once per new block and executes
+    /// forced exits from the risk monitor. Heads that arrive while a pass
+    /// runs collapse into one. A pass in progress at shutdown is finished.
+    async fn exit_loop(
+        &self,
+        mut heads: watch::Receiver<U64>,
+        mut forced: mpsc::Receiver<ForcedExit>,
+    ) -> Result<()> {
+        loop {
+            tokio::select! {
+                changed = heads.changed() => {
+                    if changed.is_err() {
+                        // The block watcher only stops on shutdown.
+                        return Ok(());
+                    }
+                    let block = *heads.borrow_and_update();
+                    if let Err(err) = self.evaluate_exits().await {
+                        error!(block = %block, error = ?err, "exit evaluation failed");
+                    }
+                }
+                Some(exit) = forced.recv() => {
+                    if let Err(err) = self.force_exit(&exit).await {
+                        error!(position = ?exit.position_id, error = ?err, "forced exit failed");
+                    }
+                }
+                _ = self.wait_for_shutdown() => return Ok(()),
+            }
+        }
+    }
+
+    /// Re-runs the risk analysis for held tokens and asks the exit loop to
+    /// close positions that no longer pass it.
+    async fn risk_loop(&self, forced: mpsc::Sender<ForcedExit>) -> Result<()> {
+        if self.config.risk.monitor_interval_secs == 0 {
+            info!("risk monitor disabled");
+            self.wait_for_shutdown().await;
+            return Ok(());
+        }
+        let mut ticker = interval(Duration::from_secs(self.config.risk.monitor_interval_secs));
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = self.wait_for_shutdown() => return Ok(()),
+            }
+            tokio::select! {
+                result = self.review_positions(&forced) => {
+                    if let Err(err) = result {
+                        error!(error = ?err, "risk review failed");
+                    }
+                }
+                _ = self.wait_for_shutdown() => return Ok(()),
+            }
+        }
+    }
+
+    /// Samples tracked pairs into the price history: discovered candidates
+    /// for a while after they were last seen, and every open position.
+    async fn history_loop(&self) -> Result<()> {
+        if self.config.history.sample_interval_secs == 0 {
+            info!("price sampler disabled");
+            self.wait_for_shutdown().await;
+            return Ok(());
+        }
+        let mut ticker = interval(Duration::from_secs(
+            self.config.history.sample_interval_secs,
+        ));
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
         loop {
-            if let Err(err) = self.tick().await {
-                error!(error = ?err, "tick failed");
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = self.wait_for_shutdown() => return Ok(()),
+            }
+            tokio::select! {
+                result = self.sample_prices() => {
+                    if let Err(err) = result {
+                        error!(error = ?err, "price sampling failed");
+                    }
+                }
+                _ = self.wait_for_shutdown() => return Ok(()),
+            }
+        }
+    }
+
+    async fn sample_prices(&self) -> Result<()> {
+        let now = time::OffsetDateTime::now_utc();
+        let track_until = now + time::Duration::minutes(self.config.history.track_minutes as i64);
+        let positions = self.portfolio.read().await.open_positions();
+        let targets = {
+            let mut history = self.history.write().await;
+            for position in &positions {
+                history.track(
+                    position.pair_address,
+                    position.token,
+                    position.base_token,
+                    matches!(position.route.pool, venue::PoolKind::V3 { .. }),
+                    track_until,
+                );
+            }
+            history.targets(now)
+        };
+
+        let mut usd_per_base: HashMap<Address, f64> = HashMap::new();
+        let mut samples = Vec::with_capacity(targets.len());
+        for target in targets {
+            let usd = match usd_per_base.get(&target.base_token) {
+                Some(usd) => *usd,
+                None => {
+                    let usd = self
+                        .trader
+                        .fetch_base_usd_price(target.base_token)
+                        .await
+                        .context("fetch base token usd price")?;
+                    usd_per_base.insert(target.base_token, usd);
+                    usd
+                }
+            };
+            match history::sample_pair(&self.trader, &target, usd).await {
+                Ok(sample) => samples.push((target.pair, sample)),
+                Err(err) => warn!(pair = ?target.pair, error = ?err, "price sample failed"),
+            }
+        }
+
+        let mut history = self.history.write().await;
+        for (pair, sample) in &samples {
+            history.record(*pair, sample);
+        }
+        history.persist()
+    }
+
+    /// Sells every open position, still within the price impact limit.
+    async fn flatten_positions(&self) {
+        let positions = self.portfolio.read().await.open_positions();
+        info!(count = positions.len(), "flattening open positions");
+        for position in positions {
+            let request = ForcedExit {
+                position_id: position.id,
+                reason: ExitReason::Manual,
+            };
+            if let Err(err) = self.force_exit(&request).await {
+                error!(position = ?position.id, error = ?err, "flatten exit failed");
+            }
+        }
+        let remaining = self.portfolio.read().await.open_positions().len();
+        if remaining > 0 {
+            warn!(remaining, "positions still open after flattening");
+        }
+    }
+
+    async fn review_positions(&self, forced: &mpsc::Sender<ForcedExit>) -> Result<()> {
+        let positions = self.portfolio.read().await.open_positions();
+        for position in positions {
+            let candidates = self
+                .scanner
+                .fetch_token_candidates(&position.token, &self.config)
+                .await
+                .context("fetch held token pools")?;
+            let Some(candidate) = candidates
+                .iter()
+                .find(|candidate| candidate.pair_address == position.pair_address)
+                .or(candidates.first())
+            else {
+                warn!(position = ?position.id, token = ?position.token, "no live pool for held token");
+                continue;
+            };
+
+            let report = self
+                .risk
+                .evaluate_candidate(candidate)
+                .await
+                .context("risk evaluation")?;
+            if !report.is_safe {
+                warn!(
+                    position = ?position.id,
+                    token = ?position.token,
+                    score = report.score,
+                    reason = ?report.flags,
+                    "held token failed risk review, requesting exit"
+                );
+                forced
+                    .send(ForcedExit {
+                        position_id: position.id,
+                        reason: ExitReason::RiskAlert,
+                    })
+                    .await
+                    .context("exit loop stopped")?;
             }
-            sleep(LOOP_INTERVAL).await;
         }
+        Ok(())
     }
 
+    /// One discovery pass followed by one exit pass.
     #[instrument(skip(self))]
     pub async fn tick(&self) -> Result<()> {
-        let mut portfolio = self.portfolio.write().await;
-        portfolio.refresh_positions(&self.trader).await?;
+        self.discover().await?;
+        self.evaluate_exits().await
+    }
 
-        if portfolio.active_positions().len() >= self.config.strategy.max_positions {
+    /// Enters new candidates. The portfolio is only locked to check holdings
+    /// and to book fills.
+    #[instrument(skip(self))]
+    pub async fn discover(&self) -> Result<()> {
+        if self.all_strategies_full().await {
             info!("max positions reached, skipping new entries");
             return Ok(());
         }
@@ -77,7 +489,23 @@ impl TradingBot {
             return Ok(());
         }
 
-        let mut analyzed: HashMap<Address, (GemCandidate, TokenRiskReport)> = HashMap::new();
+        {
+            let until = time::OffsetDateTime::now_utc()
+                + time::Duration::minutes(self.config.history.track_minutes as i64);
+            let mut history = self.history.write().await;
+            for candidate in &candidates {
+                history.track(
+                    candidate.pair_address,
+                    candidate.token_address,
+                    candidate.base_token,
+                    candidate.is_concentrated_liquidity(),
+                    until,
+                );
+            }
+        }
+
+        // One candidate per token, in the scanner's confidence order.
+        let mut analyzed: Vec<(GemCandidate, TokenRiskReport)> = Vec::new();
         for candidate in candidates {
             if self
                 .config
@@ -88,11 +516,13 @@ impl TradingBot {
                 continue;
             }
 
-            let risk_report = self
-                .risk
-                .evaluate_candidate(&candidate)
-                .await
-                .context("risk evaluation")?;
+            let risk_report = match self.risk.evaluate_candidate(&candidate).await {
+                Ok(report) => report,
+                Err(err) => {
+                    warn!(token = ?candidate.token_address, error = ?err, "risk evaluation failed, skipping candidate");
+                    continue;
+                }
+            };
 
             if !risk_report.is_safe {
                 info!(
@@ -104,73 +534,255 @@ impl TradingBot {
                 continue;
             }
 
-            analyzed.insert(candidate.token_address, (candidate, risk_report));
+            analyzed.push((candidate, risk_report));
         }
 
-        for (token, (candidate, report)) in &analyzed {
-            if portfolio.is_holding(token) {
+        for (candidate, report) in &analyzed {
+            let token = &candidate.token_address;
+            if self.is_shutting_down() {
+                info!("shutting down, skipping remaining entries");
+                break;
+            }
+            if self.all_strategies_full().await {
+                info!("max positions reached, skipping new entries");
+                break;
+            }
+            if self.portfolio.read().await.is_holding(token) {
                 continue;
             }
 
-            if !self
-                .scanner
-                .has_momentum(candidate, &self.config)
-                .await
-                .context("momentum check")?
-            {
-                info!(token = ?token, "insufficient momentum, skipping");
+            let candles = self.history.read().await.candles(&candidate.pair_address);
+            let market = MarketContext {
+                now: time::OffsetDateTime::now_utc(),
+                candles: &candles,
+            };
+            for profile in &self.strategies {
+                let (open, _) = self.portfolio.read().await.strategy_exposure(profile.id());
+                if profile.is_full(open) {
+                    continue;
+                }
+                let Some(intent) = profile.strategy.should_enter(candidate, report, &market) else {
+                    info!(token = ?token, strategy = profile.id(), "strategy passed on entry");
+                    continue;
+                };
+                match self.enter(profile, candidate, report, &intent).await {
+                    Ok(true) => break,
+                    Ok(false) => {}
+                    // The next strategy would likely hit the same error.
+                    Err(err) => {
+                        error!(token = ?token, strategy = profile.id(), error = ?err, "entry failed, skipping candidate");
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn all_strategies_full(&self) -> bool {
+        let portfolio = self.portfolio.read().await;
+        self.strategies
+            .iter()
+            .all(|profile| profile.is_full(portfolio.strategy_exposure(profile.id()).0))
+    }
+
+    /// Buys `candidate` for `profile`. `false` when the entry was skipped
+    /// for the strategy's budget, the venue or price impact.
+    async fn enter(
+        &self,
+        profile: &StrategyProfile,
+        candidate: &GemCandidate,
+        report: &TokenRiskReport,
+        intent: &EntryIntent,
+    ) -> Result<bool> {
+        let token = &candidate.token_address;
+        let size = config::eth_to_wei(intent.size_eth)?;
+        let entry_base_price = self
+            .trader
+            .fetch_base_usd_price(candidate.base_token)
+            .await
+            .context("fetch base usd price")?;
+        let (_, exposure_usd) = self.portfolio.read().await.strategy_exposure(profile.id());
+        if !profile.fits_budget(exposure_usd, intent.size_eth * entry_base_price) {
+            info!(token = ?token, strategy = profile.id(), "strategy budget exhausted, skipping");
+            return Ok(false);
+        }
+        let base_decimals = self
+            .trader
+            .token_decimals(candidate.base_token)
+            .await
+            .unwrap_or(18);
+        let Some((candidate, route)) = self.route(candidate, size).await else {
+            info!(token = ?token, "no supported venue, skipping");
+            return Ok(false);
+        };
+        let candidate = &candidate;
+        let Some(sized) = self
+            .trader
+            .fit_price_impact(
+                candidate.pair_address,
+                candidate.base_token,
+                *token,
+                size,
+                route,
+            )
+            .await
+            .context("price impact check")?
+        else {
+            info!(token = ?token, "entry price impact above limit, skipping");
+            return Ok(false);
+        };
+        let execution = self
+            .trader
+            .execute_entry(token, sized, candidate, route)
+            .await
+            .context("executing entry trade")?;
+
+        let mut portfolio = self.portfolio.write().await;
+        portfolio.add_position(Position::from_execution(
+            profile.id(),
+            candidate,
+            execution,
+            report.score,
+            intent,
+            entry_base_price,
+            base_decimals,
+        ));
+        portfolio.persist()?;
+        Ok(true)
+    }
+
+    /// The first of the candidate's pools, primary then alternates, that
+    /// resolves to a configured venue, with the candidate moved onto it.
+    async fn route(
+        &self,
+        candidate: &GemCandidate,
+        size: U256,
+    ) -> Option<(GemCandidate, SwapRoute)> {
+        let pools = std::iter::once(candidate.clone()).chain(
+            candidate
+                .alternate_pools
+                .iter()
+                .map(|pool| candidate.with_pool(pool)),
+        );
+        for pool in pools {
+            match self.trader.resolve_route(&pool, size).await {
+                Ok(route) => return Some((pool, route)),
+                Err(err) => debug!(
+                    token = ?pool.token_address,
+                    pair = ?pool.pair_address,
+                    dex = %pool.dex_id,
+                    reason = %err,
+                    "pool has no supported venue"
+                ),
+            }
+        }
+        None
+    }
+
+    /// Marks open positions to market and executes the exits whose targets
+    /// were crossed.
+    #[instrument(skip(self))]
+    pub async fn evaluate_exits(&self) -> Result<()> {
+        let positions = self.portfolio.read().await.open_positions();
+        if positions.is_empty() {
+            return Ok(());
+        }
+
+        let valuations = portfolio::value_positions(&positions, &self.trader).await?;
+        self.portfolio.write().await.record_valuations(&valuations);
+
+        info!("evaluating exit conditions");
+        let now = time::OffsetDateTime::now_utc();
+        let mut exits = Vec::new();
+        for (position, valuation) in positions.iter().zip(&valuations) {
+            let candles = self.history.read().await.candles(&position.pair_address);
+            let market = MarketContext {
+                now,
+                candles: &candles,
+            };
+            let Some(intent) = self
+                .strategy_for(position)
+                .strategy
+                .should_exit(&position.view(valuation.value_usd), &market)
+            else {
                 continue;
+            };
+            if let Some(order) = portfolio::plan_exit(
+                position,
+                intent.reason,
+                valuation.base_amount,
+                &self.trader,
+                &self.config,
+            )
+            .await?
+            {
+                exits.push(order);
             }
+        }
+        self.execute_exits(exits).await
+    }
 
-            let size = self.config.position_size_wei()?;
-            let entry_base_price = self
-                .trader
-                .fetch_base_usd_price(candidate.base_token)
-                .await
-                .context("fetch base usd price")?;
-            let base_decimals = self
-                .trader
-                .token_decimals(candidate.base_token)
-                .await
-                .unwrap_or(18);
-            let execution = self
+    /// The strategy that opened `position`, or the first one when it is no
+    /// longer configured.
+    fn strategy_for(&self, position: &Position) -> &StrategyProfile {
+        self.strategies
+            .iter()
+            .find(|profile| profile.id() == position.strategy)
+            .unwrap_or(&self.strategies[0])
+    }
+
+    async fn force_exit(&self, request: &ForcedExit) -> Result<()> {
+        let Some(position) = self.portfolio.read().await.position(&request.position_id) else {
+            return Ok(());
+        };
+        let base_amount = self
+            .trader
+            .quote_sell(
+                &position.token,
+                position.token_amount,
+                position.base_token,
+                position.route,
+            )
+            .await
+            .context("quote forced exit")?;
+        let exit = portfolio::plan_exit(
+            &position,
+            request.reason.clone(),
+            base_amount,
+            &self.trader,
+            &self.config,
+        )
+        .await?;
+        self.execute_exits(exit.into_iter().collect()).await
+    }
+
+    async fn execute_exits(&self, exits: Vec<ExitOrder>) -> Result<()> {
+        for exit in exits {
+            let execution = match self
                 .trader
-                .execute_entry(token, size, candidate)
+                .execute_exit(&exit)
                 .await
-                .context("executing entry trade")?;
-
-            portfolio.add_position(Position::from_execution(
-                candidate,
-                execution,
-                report.score,
-                self.config.strategy.take_profit_bps,
-                self.config.strategy.stop_loss_bps,
-                entry_base_price,
-                base_decimals,
-            ));
-        }
-
-        if !portfolio.positions().is_empty() {
-            info!("evaluating exit conditions");
-            let exits = portfolio
-                .generate_exit_orders(&self.trader, &self.config)
-                .await?;
-
-            for exit in exits {
-                if let Err(err) = self
-                    .trader
-                    .execute_exit(&exit)
-                    .await
-                    .context("exit execution")
-                {
+                .context("exit execution")
+            {
+                Ok(execution) => execution,
+                Err(err) => {
                     error!(position = ?exit.position_id, error = ?err, "exit failed");
-                } else {
-                    portfolio.close_position(exit.position_id)?;
+                    continue;
                 }
-            }
+            };
+            let base_price = match self.trader.fetch_base_usd_price(exit.base_token).await {
+                Ok(price) => Some(price),
+                Err(err) => {
+                    warn!(position = ?exit.position_id, error = ?err, "no base price for exit, booking the last mark");
+                    None
+                }
+            };
+            let mut portfolio = self.portfolio.write().await;
+            portfolio.settle_exit(&exit, &execution, base_price)?;
+            portfolio.persist()?;
         }
-
-        portfolio.persist()?;
         Ok(())
     }
 
@@ -183,7 +795,90 @@ impl TradingBot {
         Ok(format!("ok:{latest_block}"))
     }
 
+    pub fn rpc_status(&self) -> Vec<EndpointStatus> {
+        self.trader.provider().as_ref().as_ref().endpoint_status()
+    }
+
+    /// Revokes recorded router allowances for every token without an open
+    /// position.
+    pub async fn revoke_approvals(&self, dry_run: bool) -> Result<Vec<RevokedApproval>> {
+        let primary = self.trader.wallet_addresses()[0];
+        let held = self
+            .portfolio
+            .read()
+            .await
+            .positions()
+            .iter()
+            .map(|position| {
+                let wallet = if position.wallet.is_zero() {
+                    primary
+                } else {
+                    position.wallet
+                };
+                (wallet, position.token)
+            })
+            .collect::<HashSet<_>>();
+        self.trader.revoke_approvals(&held, dry_run).await
+    }
+
+    /// Scores every live pool of `token` against the risk policy.
+    pub async fn evaluate_token(&self, token: Address) -> Result<Vec<TokenRiskReport>> {
+        let candidates = self
+            .scanner
+            .fetch_token_candidates(&token, &self.config)
+            .await
+            .context("fetch token candidates")?;
+        let mut reports = Vec::with_capacity(candidates.len());
+        for candidate in &candidates {
+            reports.push(self.risk.evaluate_candidate(candidate).await?);
+        }
+        Ok(reports)
+    }
+
     pub async fn portfolio_snapshot(&self) -> PortfolioSnapshot {
-        self.portfolio.read().await.snapshot()
+        self.portfolio.read().await.snapshot(&self.strategy_ids())
+    }
+
+    pub async fn strategy_snapshots(&self) -> Vec<StrategySnapshot> {
+        self.portfolio
+            .read()
+            .await
+            .strategy_snapshots(&self.strategy_ids())
+    }
+
+    fn strategy_ids(&self) -> Vec<&str> {
+        self.strategies.iter().map(StrategyProfile::id).collect()
+    }
+
+    /// Sampled candles for `pair`, oldest first.
+    pub async fn candles(&self, pair: &Address) -> Vec<Candle> {
+        self.history.read().await.candles(pair)
+    }
+
+    pub async fn sampled_pairs(&self) -> Vec<Address> {
+        self.history.read().await.tracked_pairs()
+    }
+
+    pub async fn wallet_snapshots(&self) -> Result<Vec<WalletSnapshot>> {
+        let addresses = self.trader.wallet_addresses();
+        let mut balances = Vec::with_capacity(addresses.len());
+        for address in &addresses {
+            let balance = self.trader.native_balance(*address).await?;
+            balances.push(
+                ethers::utils::format_ether(balance)
+                    .parse::<f64>()
+                    .unwrap_or(0.0),
+            );
+        }
+
+        let portfolio = self.portfolio.read().await;
+        Ok(addresses
+            .into_iter()
+            .zip(balances)
+            .enumerate()
+            .map(|(index, (address, balance))| {
+                portfolio.wallet_snapshot(address, index == 0, balance)
+            })
+            .collect())
     }
 }
@@ -0,0 +1,407 @@
+use std::{collections::BTreeMap, fs, path::Path, sync::Arc};
+
+use anyhow::{Context, Result, bail, ensure};
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::SliceRandom};
+use serde::{Deserialize, Serialize};
+use tokio::{sync::Semaphore, task::JoinSet};
+
+use crate::config::BotConfig;
+
+use super::{backtest::Backtester, dataset::MarketSnapshot};
+
+/// Grids larger than this are refused; sample them with random search.
+const MAX_GRID_RUNS: usize = 10_000;
+/// Drawdown floor for the risk-adjusted score, so a lucky run without any
+/// drawdown does not divide by zero.
+const MIN_DRAWDOWN_PCT: f64 = 1.0;
+
+/// Parameter name to value for one run.
+pub type ParamSet = BTreeMap<String, f64>;
+
+/// Values one parameter may take: an explicit list, or `min`..=`max` with an
+/// optional `step`. Grid search needs a step; random search draws uniformly
+/// when there is none.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum ParamRange {
+    Values(Vec<f64>),
+    Span {
+        min: f64,
+        max: f64,
+        #[serde(default)]
+        step: Option<f64>,
+    },
+}
+
+impl ParamRange {
+    fn grid_values(&self, name: &str) -> Result<Vec<f64>> {
+        match self {
+            Self::Values(values) => Ok(values.clone()),
+            Self::Span {
+                min,
+                max,
+                step: Some(step),
+            } => {
+                let count = ((max - min) / step + 1e-9).floor() as usize + 1;
+                Ok((0..count).map(|index| min + step * index as f64).collect())
+            }
+            Self::Span { step: None, .. } => {
+                bail!("{name}: grid search needs a `step` or a list of values")
+            }
+        }
+    }
+
+    fn sample(&self, rng: &mut StdRng) -> f64 {
+        match self {
+            Self::Values(values) => *values.choose(rng).expect("validated non-empty"),
+            Self::Span {
+                min,
+                max,
+                step: Some(step),
+            } => {
+                let count = ((max - min) / step + 1e-9).floor() as usize + 1;
+                min + step * rng.gen_range(0..count) as f64
+            }
+            Self::Span {
+                min,
+                max,
+                step: None,
+            } => rng.gen_range(*min..=*max),
+        }
+    }
+}
+
+/// Parameter ranges to search, read from a TOML file with a `[parameters]`
+/// table keyed by config field name.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SearchSpace {
+    pub parameters: BTreeMap<String, ParamRange>,
+}
+
+impl SearchSpace {
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw =
+            fs::read_to_string(path).with_context(|| format!("read search space file {path:?}"))?;
+        let space: Self =
+            toml::from_str(&raw).with_context(|| format!("parse search space {path:?}"))?;
+        space.validate()?;
+        Ok(space)
+    }
+
+    fn validate(&self) -> Result<()> {
+        ensure!(
+            !self.parameters.is_empty(),
+            "search space has no parameters"
+        );
+        for (name, range) in &self.parameters {
+            match range {
+                ParamRange::Values(values) => {
+                    ensure!(!values.is_empty(), "{name}: empty value list");
+                }
+                ParamRange::Span { min, max, step } => {
+                    ensure!(min <= max, "{name}: min is above max");
+                    ensure!(
+                        step.is_none_or(|step| step > 0.0),
+                        "{name}: step must be positive"
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Every combination of the grid values.
+    pub fn grid(&self) -> Result<Vec<ParamSet>> {
+        let mut sets = vec![ParamSet::new()];
+        for (name, range) in &self.parameters {
+            let values = range.grid_values(name)?;
+            ensure!(
+                sets.len().saturating_mul(values.len()) <= MAX_GRID_RUNS,
+                "grid exceeds {MAX_GRID_RUNS} runs; use random search or coarser steps"
+            );
+            sets = sets
+                .into_iter()
+                .flat_map(|set| {
+                    values.iter().map(move |value| {
+                        let mut set = set.clone();
+                        set.insert(name.clone(), *value);
+                        set
+                    })
+                })
+                .collect();
+        }
+        Ok(sets)
+    }
+
+    /// `samples` random draws, reproducible when `seed` is given.
+    pub fn sample(&self, samples: usize, seed: Option<u64>) -> Vec<ParamSet> {
+        let mut rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        (0..samples)
+            .map(|_| {
+                self.parameters
+                    .iter()
+                    .map(|(name, range)| (name.clone(), range.sample(&mut rng)))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Backtest outcome of one parameter set.
+#[derive(Debug, Clone, Serialize)]
+pub struct OptimizationResult {
+    pub params: ParamSet,
+    /// Return over max drawdown (both in percent, drawdown floored at 1%).
+    pub score: f64,
+    /// Whether the run made enough trades to be ranked on its score.
+    pub eligible: bool,
+    pub trades: usize,
+    pub win_rate: f64,
+    pub total_pnl_usd: f64,
+    pub return_pct: f64,
+    pub max_drawdown_pct: f64,
+}
+
+pub struct Optimizer {
+    base: BotConfig,
+    starting_equity_usd: f64,
+    min_trades: usize,
+    jobs: usize,
+}
+
+impl Optimizer {
+    pub fn new(base: BotConfig, starting_equity_usd: f64, min_trades: usize, jobs: usize) -> Self {
+        Self {
+            base,
+            starting_equity_usd,
+            min_trades,
+            jobs: jobs.max(1),
+        }
+    }
+
+    /// Backtests every parameter set, `jobs` at a time on blocking threads,
+    /// and returns the results best first. Runs with fewer than `min_trades` trades rank
+    /// below all others.
+    pub async fn run(
+        &self,
+        param_sets: Vec<ParamSet>,
+        snapshots: Arc<Vec<MarketSnapshot>>,
+    ) -> Result<Vec<OptimizationResult>> {
+        let permits = Arc::new(Semaphore::new(self.jobs));
+        let mut runs = JoinSet::new();
+        for params in param_sets {
+            let mut config = self.base.clone();
+            for (name, value) in &params {
+                apply_param(&mut config, name, *value)?;
+            }
+            let permit = permits.clone().acquire_owned().await?;
+            let snapshots = snapshots.clone();
+            let starting_equity_usd = self.starting_equity_usd;
+            let min_trades = self.min_trades;
+            runs.spawn_blocking(move || {
+                let _permit = permit;
+                let report = Backtester::new(config, starting_equity_usd)?.run(&snapshots)?;
+                anyhow::Ok(OptimizationResult {
+                    score: report.return_pct / report.max_drawdown_pct.max(MIN_DRAWDOWN_PCT),
+                    eligible: report.trades.len() >= min_trades,
+                    trades: report.trades.len(),
+                    win_rate: report.win_rate,
+                    total_pnl_usd: report.total_pnl_usd,
+                    return_pct: report.return_pct,
+                    max_drawdown_pct: report.max_drawdown_pct,
+                    params,
+                })
+            });
+        }
+
+        let mut results = Vec::new();
+        while let Some(joined) = runs.join_next().await {
+            results.push(joined.context("backtest task panicked")??);
+        }
+        results.sort_by(|a, b| {
+            b.eligible
+                .cmp(&a.eligible)
+                .then(b.score.total_cmp(&a.score))
+        });
+        Ok(results)
+    }
+}
+
+/// Sets the strategy or risk field `name` to `value`, rounding for integer
+/// fields.
+fn apply_param(config: &mut BotConfig, name: &str, value: f64) -> Result<()> {
+    let strategy = &mut config.strategy;
+    let risk = &mut config.risk;
+    match name {
+        "max_positions" => strategy.max_positions = value.round() as usize,
+        "position_size_eth" => strategy.position_size_eth = value,
+        "take_profit_bps" => strategy.take_profit_bps = value.round() as u32,
+        "stop_loss_bps" => strategy.stop_loss_bps = value.round() as u32,
+        "min_liquidity_usd" => strategy.min_liquidity_usd = value,
+        "min_daily_volume_usd" => strategy.min_daily_volume_usd = value,
+        "min_age_minutes" => strategy.min_age_minutes = value.round() as u64,
+        "price_momentum_window_minutes" => {
+            strategy.price_momentum_window_minutes = value.round() as u64
+        }
+        "momentum_horizon_minutes" => {
+            strategy.momentum.horizon_minutes = Some(value.round() as u64)
+        }
+        "min_momentum_score" => strategy.momentum.min_score = value,
+        "min_buy_pressure" => strategy.momentum.min_buy_pressure = value,
+        "min_volume_acceleration" => strategy.momentum.min_volume_acceleration = value,
+        "min_rsi" => strategy.indicators.min_rsi = Some(value),
+        "max_rsi" => strategy.indicators.max_rsi = Some(value),
+        "max_vwap_deviation_pct" => strategy.indicators.max_vwap_deviation_pct = Some(value),
+        "atr_stop_multiplier" => strategy.indicators.atr_stop_multiplier = value,
+        "max_top_holder_percent" => risk.max_top_holder_percent = value,
+        "min_lock_ratio" => risk.min_lock_ratio = value,
+        "min_holder_count" => risk.min_holder_count = value.round() as u64,
+        "min_renounced_score" => risk.min_renounced_score = value,
+        "min_safety_score" => risk.min_safety_score = value,
+        _ => match name.strip_prefix("momentum_weight_") {
+            Some(window) => {
+                strategy
+                    .momentum
+                    .price_weights
+                    .insert(window.parse()?, value);
+            }
+            None => bail!("unknown parameter {name}"),
+        },
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::config::MarketWindow;
+
+    use super::*;
+
+    fn space(raw: &str) -> SearchSpace {
+        let space: SearchSpace = toml::from_str(raw).unwrap();
+        space.validate().unwrap();
+        space
+    }
+
+    fn base_config() -> BotConfig {
+        serde_json::from_value(json!({
+            "chain": "mainnet",
+            "rpc": { "http_urls": ["http://127.0.0.1:8545"] },
+            "strategy": { "max_positions": 1, "position_size_eth": 0.1 },
+            "exchange": {
+                "venues": [{
+                    "dex_id": "uniswap",
+                    "protocol": "uniswap_v2",
+                    "router_address": "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D"
+                }],
+                "base_tokens": ["0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"]
+            },
+            "risk": {}
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn grid_crosses_every_value() {
+        let space = space(
+            r#"
+            [parameters]
+            take_profit_bps = [1500, 2500]
+            stop_loss_bps = { min = 500, max = 1000, step = 250 }
+            "#,
+        );
+
+        let grid = space.grid().unwrap();
+
+        assert_eq!(grid.len(), 6);
+        let pairs = grid
+            .iter()
+            .map(|set| (set["take_profit_bps"], set["stop_loss_bps"]))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            pairs,
+            [
+                (1500.0, 500.0),
+                (2500.0, 500.0),
+                (1500.0, 750.0),
+                (2500.0, 750.0),
+                (1500.0, 1000.0),
+                (2500.0, 1000.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn grid_needs_a_step() {
+        let space = space("[parameters]\nmin_rsi = { min = 30, max = 60 }");
+        assert!(space.grid().is_err());
+    }
+
+    #[test]
+    fn seeded_samples_stay_in_range_and_repeat() {
+        let space = space(
+            r#"
+            [parameters]
+            min_rsi = { min = 30, max = 60 }
+            stop_loss_bps = { min = 500, max = 1000, step = 250 }
+            max_positions = [2, 4]
+            "#,
+        );
+
+        let samples = space.sample(20, Some(7));
+
+        assert_eq!(samples.len(), 20);
+        assert_eq!(samples, space.sample(20, Some(7)));
+        for set in &samples {
+            assert!((30.0..=60.0).contains(&set["min_rsi"]));
+            assert!([500.0, 750.0, 1000.0].contains(&set["stop_loss_bps"]));
+            assert!([2.0, 4.0].contains(&set["max_positions"]));
+        }
+    }
+
+    #[test]
+    fn apply_param_sets_config_fields() {
+        let mut config = base_config();
+
+        apply_param(&mut config, "stop_loss_bps", 749.6).unwrap();
+        apply_param(&mut config, "min_rsi", 40.0).unwrap();
+        apply_param(&mut config, "min_lock_ratio", 0.8).unwrap();
+        apply_param(&mut config, "momentum_weight_h6", 0.15).unwrap();
+
+        assert_eq!(config.strategy.stop_loss_bps, 750);
+        assert_eq!(config.strategy.indicators.min_rsi, Some(40.0));
+        assert_eq!(config.risk.min_lock_ratio, 0.8);
+        assert_eq!(
+            config.strategy.momentum.price_weights[&MarketWindow::H6],
+            0.15
+        );
+    }
+
+    #[test]
+    fn apply_param_rejects_unknown_names() {
+        let mut config = base_config();
+
+        let err = apply_param(&mut config, "take_profit", 1.0).unwrap_err();
+        assert!(err.to_string().contains("unknown parameter take_profit"));
+        assert!(apply_param(&mut config, "momentum_weight_h2", 0.1).is_err());
+    }
+
+    #[tokio::test]
+    async fn optimizer_runs_every_set() {
+        let space = space("[parameters]\ntake_profit_bps = [1500, 2500, 3500]");
+        let optimizer = Optimizer::new(base_config(), 10_000.0, 1, 2);
+
+        let results = optimizer
+            .run(space.grid().unwrap(), Arc::new(Vec::new()))
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|result| !result.eligible));
+    }
+}
@@ -0,0 +1,298 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result, anyhow};
+use ethers::{prelude::*, providers::Provider};
+use serde::Deserialize;
+use time::OffsetDateTime;
+use tracing::{debug, error, warn};
+
+use crate::config::{BotConfig, OracleConfig, PriceSource};
+
+use super::{
+    http::HttpClient,
+    rpc::RpcPool,
+    trader::{Erc20, pool_price},
+};
+
+abigen!(
+    ChainlinkAggregator,
+    r#"[
+        {"inputs":[],"name":"decimals","outputs":[{"internalType":"uint8","name":"","type":"uint8"}],"stateMutability":"view","type":"function"},
+        {"inputs":[],"name":"latestRoundData","outputs":[{"internalType":"uint80","name":"roundId","type":"uint80"},{"internalType":"int256","name":"answer","type":"int256"},{"internalType":"uint256","name":"startedAt","type":"uint256"},{"internalType":"uint256","name":"updatedAt","type":"uint256"},{"internalType":"uint80","name":"answeredInRound","type":"uint80"}],"stateMutability":"view","type":"function"}
+    ]"#
+);
+
+/// Base token USD prices read on-chain from Chainlink aggregators or
+/// stablecoin pools, cross-checked against DefiLlama.
+///
+/// When every source fails the last good price is reused, so position
+/// marks, and with them stop-losses, keep working through an outage.
+pub struct PriceOracle {
+    config: OracleConfig,
+    feeds: HashMap<Address, PriceSource>,
+    llama: DefiLlama,
+    provider: Arc<Provider<RpcPool>>,
+    /// Base tokens with a cross-check in flight.
+    cross_checks: Arc<Mutex<HashSet<Address>>>,
+    /// Decimals of tokens and aggregators, which never change.
+    decimals: Mutex<HashMap<Address, u8>>,
+    last_good: Mutex<HashMap<Address, (f64, Instant)>>,
+}
+
+/// One on-chain price and, for Chainlink, how old the answer is.
+struct Reading {
+    price: f64,
+    age: Option<Duration>,
+}
+
+impl PriceOracle {
+    pub fn new(config: &BotConfig, provider: Arc<Provider<RpcPool>>, http: HttpClient) -> Self {
+        Self {
+            feeds: config
+                .oracle
+                .feeds
+                .iter()
+                .map(|feed| (feed.base_token, feed.source.clone()))
+                .collect(),
+            config: config.oracle.clone(),
+            llama: DefiLlama {
+                http,
+                url: config.api.defillama_url.clone(),
+                chain: config.chain_as_str(),
+            },
+            provider,
+            cross_checks: Arc::new(Mutex::new(HashSet::new())),
+            decimals: Mutex::new(HashMap::new()),
+            last_good: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn base_usd_price(&self, base_token: Address) -> Result<f64> {
+        let Some(source) = self.feeds.get(&base_token) else {
+            return match self.llama.price(base_token).await {
+                Ok(price) => Ok(self.remember(base_token, price)),
+                Err(err) => self.last_good(base_token, err),
+            };
+        };
+
+        let reading = match self.read(source, base_token).await {
+            Ok(reading) => reading,
+            Err(err) => {
+                warn!(?base_token, error = ?err, "on-chain base price unavailable");
+                if self.config.cross_check {
+                    match self.llama.price(base_token).await {
+                        Ok(price) => return Ok(self.remember(base_token, price)),
+                        Err(llama_err) => debug!(error = ?llama_err, "defillama fallback failed"),
+                    }
+                }
+                return self.last_good(base_token, err);
+            }
+        };
+
+        if let Some(age) = reading
+            .age
+            .filter(|age| age.as_secs() > self.config.max_age_secs)
+        {
+            warn!(
+                ?base_token,
+                age_secs = age.as_secs(),
+                max_age_secs = self.config.max_age_secs,
+                "stale chainlink base price"
+            );
+        }
+        if self.config.cross_check {
+            self.cross_check(base_token, reading.price);
+        }
+        Ok(self.remember(base_token, reading.price))
+    }
+
+    async fn read(&self, source: &PriceSource, base_token: Address) -> Result<Reading> {
+        match source {
+            PriceSource::Chainlink { aggregator } => {
+                let feed = ChainlinkAggregator::new(*aggregator, self.provider.clone());
+                let (_, answer, _, updated_at, _) = feed
+                    .latest_round_data()
+                    .call()
+                    .await
+                    .context("read chainlink latestRoundData")?;
+                if answer <= I256::zero() {
+                    return Err(anyhow!("chainlink feed {aggregator:?} answered {answer}"));
+                }
+                let decimals = self.decimals(*aggregator, true).await?;
+                let price = answer.to_string().parse::<f64>()? / 10f64.powi(decimals.into());
+                let now = OffsetDateTime::now_utc().unix_timestamp().max(0) as u64;
+                Ok(Reading {
+                    price,
+                    age: Some(Duration::from_secs(
+                        now.saturating_sub(updated_at.low_u64()),
+                    )),
+                })
+            }
+            PriceSource::StablePair {
+                pair,
+                stablecoin,
+                concentrated,
+            } => {
+                let stable_per_base =
+                    pool_price(&self.provider, *pair, base_token, *concentrated).await?;
+                let base_decimals = self.decimals(base_token, false).await?;
+                let stable_decimals = self.decimals(*stablecoin, false).await?;
+                Ok(Reading {
+                    price: stable_per_base
+                        * 10f64.powi(i32::from(base_decimals) - i32::from(stable_decimals)),
+                    age: None,
+                })
+            }
+        }
+    }
+
+    /// Raises an alarm when DefiLlama disagrees with the on-chain price by
+    /// more than `max_divergence_bps`. DefiLlama being down is not an alarm.
+    ///
+    /// Runs in the background, at most once per base token at a time, so a
+    /// slow DefiLlama never holds up marks and stop-losses.
+    fn cross_check(&self, base_token: Address, onchain: f64) {
+        if !self
+            .cross_checks
+            .lock()
+            .expect("cross-check set poisoned")
+            .insert(base_token)
+        {
+            return;
+        }
+        let llama = self.llama.clone();
+        let in_flight = self.cross_checks.clone();
+        let max_divergence_bps = self.config.max_divergence_bps;
+        tokio::spawn(async move {
+            match llama.price(base_token).await {
+                Ok(reference) => {
+                    let divergence_bps = (onchain - reference).abs() / reference * 10_000.0;
+                    if divergence_bps > max_divergence_bps {
+                        warn!(
+                            ?base_token,
+                            onchain,
+                            defillama = reference,
+                            divergence_bps,
+                            "base price diverges from defillama"
+                        );
+                    }
+                }
+                Err(err) => debug!(?base_token, error = ?err, "defillama cross-check skipped"),
+            }
+            in_flight
+                .lock()
+                .expect("cross-check set poisoned")
+                .remove(&base_token);
+        });
+    }
+
+    async fn decimals(&self, contract: Address, aggregator: bool) -> Result<u8> {
+        if let Some(decimals) = self
+            .decimals
+            .lock()
+            .expect("decimals cache poisoned")
+            .get(&contract)
+        {
+            return Ok(*decimals);
+        }
+        let decimals = if aggregator {
+            ChainlinkAggregator::new(contract, self.provider.clone())
+                .decimals()
+                .call()
+                .await
+                .context("read chainlink decimals")?
+        } else {
+            Erc20::new(contract, self.provider.clone())
+                .decimals()
+                .call()
+                .await
+                .context("read token decimals")?
+        };
+        self.decimals
+            .lock()
+            .expect("decimals cache poisoned")
+            .insert(contract, decimals);
+        Ok(decimals)
+    }
+
+    fn remember(&self, base_token: Address, price: f64) -> f64 {
+        self.last_good
+            .lock()
+            .expect("price cache poisoned")
+            .insert(base_token, (price, Instant::now()));
+        price
+    }
+
+    /// The last good price after every source failed with `err`.
+    fn last_good(&self, base_token: Address, err: anyhow::Error) -> Result<f64> {
+        let Some((price, at)) = self
+            .last_good
+            .lock()
+            .expect("price cache poisoned")
+            .get(&base_token)
+            .copied()
+        else {
+            return Err(err.context(format!("no price for base token {base_token:?}")));
+        };
+        let age = at.elapsed();
+        if age.as_secs() > self.config.max_age_secs {
+            error!(
+                ?base_token,
+                price,
+                age_secs = age.as_secs(),
+                "every base price source failed, reusing a stale price"
+            );
+        } else {
+            warn!(
+                ?base_token,
+                price,
+                age_secs = age.as_secs(),
+                error = ?err,
+                "every base price source failed, reusing the last good price"
+            );
+        }
+        Ok(price)
+    }
+}
+
+/// DefiLlama's current-price endpoint, behind the shared HTTP client.
+#[derive(Clone)]
+struct DefiLlama {
+    http: HttpClient,
+    url: String,
+    chain: &'static str,
+}
+
+impl DefiLlama {
+    async fn price(&self, base_token: Address) -> Result<f64> {
+        let key = format!("{}:{base_token:?}", self.chain);
+        let cache_key = format!("llama:{key}");
+        if let Some(price) = self.http.cached(&cache_key) {
+            return Ok(price);
+        }
+
+        let url = format!("{}/prices/current/{key}", self.url);
+        let resp: LlamaPriceResponse = self.http.get_json(&url).await?;
+        let price = resp
+            .coins
+            .get(&key)
+            .map(|price| price.price)
+            .ok_or_else(|| anyhow!("missing price data for base token"))?;
+        self.http.store(&cache_key, &price, self.http.market_ttl());
+        Ok(price)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct LlamaPriceResponse {
+    coins: HashMap<String, LlamaPriceEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LlamaPriceEntry {
+    price: f64,
+}
@@ -0,0 +1,393 @@
+use std::{collections::HashSet, fs, path::Path};
+
+use anyhow::{Context, Result, bail, ensure};
+use serde::{Deserialize, Serialize};
+
+use crate::config::BotConfig;
+
+use super::{risk::GoPlusTokenSecurity, scanner::GemCandidate};
+
+/// The policy used without `RISK_POLICY_FILE`: the original scoring, with
+/// thresholds taken from the environment.
+const DEFAULT_POLICY: &str = include_str!("../../risk_policy.toml");
+
+/// Normalized fields rules can test. Booleans are `1` or `0`. GoPlus fields
+/// are missing when GoPlus has no report for the token.
+pub const FIELDS: &[&str] = &[
+    "liquidity_usd",
+    "volume_24h_usd",
+    "fdv_usd",
+    "locked_liquidity_ratio",
+    "holder_count",
+    "contract_renounced_score",
+    "dexscreener_flag_count",
+    "goplus_report",
+    "is_honeypot",
+    "trading_disabled",
+    "can_take_back_ownership",
+    "is_proxy",
+    "cannot_sell_all",
+    "hidden_owner",
+    "is_blacklisted",
+    "is_open_source",
+    "buy_tax",
+    "sell_tax",
+    "max_tax",
+    "top10_holder_percent",
+    "goplus_holder_count",
+];
+
+/// Config thresholds a rule can compare against by name, so the env
+/// settings and the optimizer keep applying under a custom policy.
+pub const PARAMS: &[&str] = &[
+    "min_liquidity_usd",
+    "min_daily_volume_usd",
+    "min_lock_ratio",
+    "min_holder_count",
+    "max_top_holder_percent",
+    "min_renounced_score",
+    "min_safety_score",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Comparison {
+    #[serde(rename = ">=")]
+    AtLeast,
+    #[serde(rename = "<=")]
+    AtMost,
+    #[serde(rename = ">")]
+    Above,
+    #[serde(rename = "<")]
+    Below,
+    #[serde(rename = "==")]
+    Equal,
+    #[serde(rename = "!=")]
+    NotEqual,
+}
+
+impl Comparison {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::AtLeast => ">=",
+            Self::AtMost => "<=",
+            Self::Above => ">",
+            Self::Below => "<",
+            Self::Equal => "==",
+            Self::NotEqual => "!=",
+        }
+    }
+
+    fn holds(self, observed: f64, threshold: f64) -> bool {
+        match self {
+            Self::AtLeast => observed >= threshold,
+            Self::AtMost => observed <= threshold,
+            Self::Above => observed > threshold,
+            Self::Below => observed < threshold,
+            Self::Equal => observed == threshold,
+            Self::NotEqual => observed != threshold,
+        }
+    }
+}
+
+/// A literal or the name of a config threshold in [`PARAMS`].
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Threshold {
+    Flag(bool),
+    Number(f64),
+    Param(String),
+}
+
+impl Threshold {
+    fn resolve(&self, config: &BotConfig) -> f64 {
+        match self {
+            Self::Flag(flag) => f64::from(u8::from(*flag)),
+            Self::Number(value) => *value,
+            Self::Param(name) => param_value(name, config).unwrap_or(f64::NAN),
+        }
+    }
+}
+
+/// What a rule does when its field has no value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WhenMissing {
+    #[default]
+    Fail,
+    Pass,
+    /// Neither scores nor flags.
+    Skip,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RiskRule {
+    pub id: String,
+    pub field: String,
+    pub op: Comparison,
+    pub value: Threshold,
+    /// Added to the score when the condition holds.
+    #[serde(default)]
+    pub weight: f64,
+    /// A failed critical rule marks the token unsafe whatever its score.
+    #[serde(default)]
+    pub critical: bool,
+    /// Flag raised when the condition fails; defaults to the rule id.
+    #[serde(default)]
+    pub flag: Option<String>,
+    #[serde(default)]
+    pub missing: WhenMissing,
+    /// Append the observed value to the flag.
+    #[serde(default)]
+    pub flag_value: bool,
+}
+
+impl RiskRule {
+    fn flag(&self, observed: Option<f64>) -> String {
+        let mut flag = self.flag.clone().unwrap_or_else(|| self.id.clone());
+        if self.critical {
+            flag.insert_str(0, "critical:");
+        }
+        if let (true, Some(observed)) = (self.flag_value, observed) {
+            flag.push_str(&format!(":{}", (observed * 100.0).round() / 100.0));
+        }
+        flag
+    }
+}
+
+/// Declarative risk scoring: every rule that holds adds its weight, and a
+/// token is safe when the score reaches `pass_score` and no critical rule
+/// failed.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RiskPolicy {
+    pub pass_score: Threshold,
+    pub rules: Vec<RiskRule>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Only costs the rule's points when it fails.
+    Warning,
+    /// Rejects the token when it fails.
+    Critical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleStatus {
+    Passed,
+    Failed,
+    /// The field had no value and the rule is set to `missing = "skip"`.
+    Skipped,
+}
+
+/// How one rule scored a token.
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleOutcome {
+    pub rule: String,
+    pub field: String,
+    /// `None` when the field had no value.
+    pub observed: Option<f64>,
+    pub op: Comparison,
+    pub threshold: f64,
+    pub points: f64,
+    pub weight: f64,
+    pub severity: Severity,
+    pub status: RuleStatus,
+    /// Flag raised by a failed rule.
+    pub flag: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PolicyVerdict {
+    pub score: f64,
+    pub pass_score: f64,
+    pub is_safe: bool,
+    pub flags: Vec<String>,
+    /// One outcome per rule, in policy order.
+    pub rules: Vec<RuleOutcome>,
+}
+
+impl RiskPolicy {
+    /// Reads and validates the policy at `path`, or the default policy.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let policy: Self = match path {
+            Some(path) => {
+                let raw = fs::read_to_string(path)
+                    .with_context(|| format!("read risk policy {path:?}"))?;
+                toml::from_str(&raw).with_context(|| format!("parse risk policy {path:?}"))?
+            }
+            None => toml::from_str(DEFAULT_POLICY).context("parse default risk policy")?,
+        };
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    fn validate(&self) -> Result<()> {
+        validate_threshold("pass_score", &self.pass_score)?;
+        ensure!(
+            !matches!(self.pass_score, Threshold::Flag(_)),
+            "pass_score must be a number"
+        );
+        let mut ids = HashSet::new();
+        for rule in &self.rules {
+            ensure!(!rule.id.trim().is_empty(), "risk rule without an id");
+            ensure!(ids.insert(&rule.id), "duplicate risk rule {}", rule.id);
+            ensure!(
+                FIELDS.contains(&rule.field.as_str()),
+                "risk rule {}: unknown field {}, expected one of: {}",
+                rule.id,
+                rule.field,
+                FIELDS.join(", ")
+            );
+            validate_threshold(&rule.id, &rule.value)?;
+            if matches!(rule.value, Threshold::Flag(_)) {
+                ensure!(
+                    matches!(rule.op, Comparison::Equal | Comparison::NotEqual),
+                    "risk rule {}: booleans only compare with == or !=",
+                    rule.id
+                );
+            }
+            ensure!(
+                rule.weight.is_finite(),
+                "risk rule {}: weight must be a number",
+                rule.id
+            );
+        }
+        Ok(())
+    }
+
+    pub fn evaluate(
+        &self,
+        candidate: &GemCandidate,
+        security: Option<&GoPlusTokenSecurity>,
+        config: &BotConfig,
+    ) -> PolicyVerdict {
+        let mut score = 0.0;
+        let mut flags = Vec::new();
+        let mut outcomes = Vec::with_capacity(self.rules.len());
+        let mut vetoed = false;
+
+        for rule in &self.rules {
+            let observed = field_value(&rule.field, candidate, security);
+            let status = match (observed, rule.missing) {
+                (Some(observed), _) if rule.op.holds(observed, rule.value.resolve(config)) => {
+                    RuleStatus::Passed
+                }
+                (None, WhenMissing::Pass) => RuleStatus::Passed,
+                (None, WhenMissing::Skip) => RuleStatus::Skipped,
+                _ => RuleStatus::Failed,
+            };
+            let mut outcome = RuleOutcome {
+                rule: rule.id.clone(),
+                field: rule.field.clone(),
+                observed,
+                op: rule.op,
+                threshold: rule.value.resolve(config),
+                points: 0.0,
+                weight: rule.weight,
+                severity: if rule.critical {
+                    Severity::Critical
+                } else {
+                    Severity::Warning
+                },
+                status,
+                flag: None,
+            };
+            match status {
+                RuleStatus::Passed => {
+                    score += rule.weight;
+                    outcome.points = rule.weight;
+                }
+                RuleStatus::Failed => {
+                    vetoed |= rule.critical;
+                    let flag = rule.flag(observed);
+                    flags.push(flag.clone());
+                    outcome.flag = Some(flag);
+                }
+                RuleStatus::Skipped => {}
+            }
+            outcomes.push(outcome);
+        }
+
+        let pass_score = self.pass_score.resolve(config);
+        PolicyVerdict {
+            score,
+            pass_score,
+            is_safe: !vetoed && score >= pass_score,
+            flags,
+            rules: outcomes,
+        }
+    }
+}
+
+fn validate_threshold(rule: &str, threshold: &Threshold) -> Result<()> {
+    match threshold {
+        Threshold::Param(name) if !PARAMS.contains(&name.as_str()) => bail!(
+            "risk rule {rule}: unknown threshold {name}, expected a number or one of: {}",
+            PARAMS.join(", ")
+        ),
+        Threshold::Number(value) if !value.is_finite() => {
+            bail!("risk rule {rule}: threshold must be finite")
+        }
+        _ => Ok(()),
+    }
+}
+
+fn param_value(name: &str, config: &BotConfig) -> Option<f64> {
+    let (strategy, risk) = (&config.strategy, &config.risk);
+    Some(match name {
+        "min_liquidity_usd" => strategy.min_liquidity_usd,
+        "min_daily_volume_usd" => strategy.min_daily_volume_usd,
+        "min_lock_ratio" => risk.min_lock_ratio,
+        "min_holder_count" => risk.min_holder_count as f64,
+        "max_top_holder_percent" => risk.max_top_holder_percent,
+        "min_renounced_score" => risk.min_renounced_score,
+        "min_safety_score" => risk.min_safety_score,
+        _ => return None,
+    })
+}
+
+fn field_value(
+    name: &str,
+    candidate: &GemCandidate,
+    security: Option<&GoPlusTokenSecurity>,
+) -> Option<f64> {
+    let flag = |set: bool| f64::from(u8::from(set));
+    match name {
+        "liquidity_usd" => Some(candidate.liquidity_usd),
+        "volume_24h_usd" => Some(candidate.volume24h_usd),
+        "fdv_usd" => Some(candidate.fdv_usd),
+        "locked_liquidity_ratio" => candidate.locked_liquidity_ratio,
+        "holder_count" => candidate.holder_count.map(|count| count as f64),
+        "contract_renounced_score" => candidate.contract_renounced_score,
+        "dexscreener_flag_count" => Some(candidate.safety_flags.len() as f64),
+        "goplus_report" => Some(flag(security.is_some())),
+        _ => {
+            let security = security?;
+            match name {
+                "is_honeypot" => Some(flag(security.is_honeypot())),
+                "trading_disabled" => Some(flag(security.trading_disabled())),
+                "can_take_back_ownership" => Some(flag(security.can_take_back_ownership())),
+                "is_proxy" => Some(flag(security.is_proxy())),
+                "cannot_sell_all" => Some(flag(security.cannot_sell_all())),
+                "hidden_owner" => Some(flag(security.hidden_owner())),
+                "is_blacklisted" => Some(flag(security.is_blacklisted())),
+                "is_open_source" => Some(flag(security.is_open_source())),
+                "buy_tax" => security.buy_tax(),
+                "sell_tax" => security.sell_tax(),
+                "max_tax" => Some(
+                    security
+                        .buy_tax()
+                        .unwrap_or_default()
+                        .max(security.sell_tax().unwrap_or_default()),
+                ),
+                "top10_holder_percent" => security.top10_holder_percent(),
+                "goplus_holder_count" => security.holder_count(),
+                _ => None,
+            }
+        }
+    }
+}
@@ -1,16 +1,23 @@
-use std::{collections::HashMap, fs, path::PathBuf};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::PathBuf,
+};
 
 use anyhow::{Context, Result};
 use ethers::types::{Address, U256};
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
+use tracing::warn;
 use uuid::Uuid;
 
 use crate::config::BotConfig;
 
 use super::{
     scanner::GemCandidate,
-    trader::{ExecutionResult, ExitOrder, ExitReason, Trader},
+    strategy::{EntryIntent, PositionView},
+    trader::{ExecutionResult, ExitOrder, ExitReason, ImpactCheck, Trader},
+    venue::SwapRoute,
 };
 
 const STORAGE_FILE: &str = "portfolio_state.json";
@@ -21,6 +28,15 @@ pub struct Position {
     pub token: Address,
     pub base_token: Address,
     pub token_symbol: String,
+    #[serde(default)]
+    pub wallet: Address,
+    /// Id of the strategy that opened the position.
+    #[serde(default)]
+    pub strategy: String,
+    #[serde(default)]
+    pub pair_address: Address,
+    #[serde(default)]
+    pub route: SwapRoute,
     pub base_spent: U256,
     pub token_amount: U256,
     pub entry_token_price_usd: f64,
@@ -37,11 +53,11 @@ pub struct Position {
 
 impl Position {
     pub fn from_execution(
+        strategy: &str,
         candidate: &GemCandidate,
         execution: ExecutionResult,
         risk_score: f64,
-        take_profit_bps: u32,
-        stop_loss_bps: u32,
+        intent: &EntryIntent,
         entry_base_price_usd: f64,
         base_token_decimals: u8,
     ) -> Self {
@@ -56,6 +72,10 @@ impl Position {
             token: candidate.token_address,
             base_token: candidate.base_token,
             token_symbol: candidate.token_symbol.clone(),
+            wallet: execution.wallet,
+            strategy: strategy.to_string(),
+            pair_address: candidate.pair_address,
+            route: execution.route,
             base_spent: execution.base_spent,
             token_amount: execution.tokens_acquired,
             entry_token_price_usd,
@@ -65,8 +85,8 @@ impl Position {
             last_value_usd: entry_value_usd,
             last_updated_at: entry_timestamp,
             risk_score,
-            take_profit_bps,
-            stop_loss_bps,
+            take_profit_bps: intent.take_profit_bps,
+            stop_loss_bps: intent.stop_loss_bps,
             entry_tx: format!("{:?}", execution.tx_hash),
         }
     }
@@ -74,11 +94,40 @@ impl Position {
     pub fn entry_value_usd(&self) -> f64 {
         format_amount(self.base_spent, self.base_token_decimals) * self.entry_base_price_usd
     }
+
+    /// The position as exit rules see it when selling it returns `value_usd`.
+    pub fn view(&self, value_usd: f64) -> PositionView {
+        PositionView {
+            token: self.token,
+            entry_at: self.entry_timestamp,
+            entry_value_usd: self.entry_value_usd(),
+            value_usd,
+            take_profit_bps: self.take_profit_bps,
+            stop_loss_bps: self.stop_loss_bps,
+        }
+    }
+}
+
+/// On-disk portfolio. Files written before realized PnL was tracked hold
+/// just the position map.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum StoredPortfolio {
+    Current {
+        positions: HashMap<Uuid, Position>,
+        #[serde(default)]
+        realized_pnl_usd: BTreeMap<String, f64>,
+    },
+    Positions(HashMap<Uuid, Position>),
 }
 
 #[derive(Debug, Default)]
 pub struct Portfolio {
     positions: HashMap<Uuid, Position>,
+    /// Realized PnL per strategy id: the base tokens each exit redeemed at
+    /// the base price when it settled, or the last mark when that price was
+    /// unavailable, less the sold share of the cost basis.
+    realized_pnl_usd: BTreeMap<String, f64>,
     storage_path: PathBuf,
 }
 
@@ -87,26 +136,56 @@ impl Portfolio {
         let path = PathBuf::from(STORAGE_FILE);
         if !path.exists() {
             return Ok(Self {
-                positions: HashMap::new(),
                 storage_path: path,
+                ..Self::default()
             });
         }
 
         let data = fs::read_to_string(&path).context("read portfolio file")?;
-        let positions: HashMap<Uuid, Position> =
-            serde_json::from_str(&data).context("parse portfolio json")?;
+        let (positions, realized_pnl_usd) =
+            match serde_json::from_str(&data).context("parse portfolio json")? {
+                StoredPortfolio::Current {
+                    positions,
+                    realized_pnl_usd,
+                } => (positions, realized_pnl_usd),
+                StoredPortfolio::Positions(positions) => (positions, BTreeMap::new()),
+            };
 
         Ok(Self {
             positions,
+            realized_pnl_usd,
             storage_path: path,
         })
     }
 
     pub fn persist(&self) -> Result<()> {
-        let json = serde_json::to_string_pretty(&self.positions)?;
+        let json = serde_json::to_string_pretty(&StoredPortfolio::Current {
+            positions: self.positions.clone(),
+            realized_pnl_usd: self.realized_pnl_usd.clone(),
+        })?;
         fs::write(&self.storage_path, json).context("write portfolio file")
     }
 
+    /// Hands positions without a strategy, opened before strategies were
+    /// tagged, to `strategy`.
+    pub fn assign_untagged(&mut self, strategy: &str) {
+        for position in self.positions.values_mut() {
+            if position.strategy.is_empty() {
+                position.strategy = strategy.to_string();
+            }
+        }
+    }
+
+    /// Open positions of `strategy` and their total entry value in USD.
+    pub fn strategy_exposure(&self, strategy: &str) -> (usize, f64) {
+        self.positions
+            .values()
+            .filter(|p| p.strategy == strategy)
+            .fold((0, 0.0), |(count, value), p| {
+                (count + 1, value + p.entry_value_usd())
+            })
+    }
+
     pub fn add_position(&mut self, position: Position) {
         self.positions.insert(position.id, position);
     }
@@ -123,125 +202,69 @@ impl Portfolio {
         self.positions.values().any(|p| &p.token == token)
     }
 
-    pub async fn refresh_positions(&mut self, trader: &Trader) -> Result<()> {
-        let mut base_price_cache: HashMap<Address, f64> = HashMap::new();
-        let mut base_decimal_cache: HashMap<Address, u8> = HashMap::new();
-        for position in self.positions.values_mut() {
-            let base_price = if let Some(price) = base_price_cache.get(&position.base_token) {
-                *price
-            } else {
-                let price = trader
-                    .fetch_base_usd_price(position.base_token)
-                    .await
-                    .context("fetch base usd price")?;
-                base_price_cache.insert(position.base_token, price);
-                price
-            };
-
-            let base_decimals = if let Some(decimals) = base_decimal_cache.get(&position.base_token)
-            {
-                *decimals
-            } else {
-                let decimals = trader
-                    .token_decimals(position.base_token)
-                    .await
-                    .unwrap_or(position.base_token_decimals);
-                base_decimal_cache.insert(position.base_token, decimals);
-                decimals
-            };
-
-            let base_amount = trader
-                .quote_sell(&position.token, position.token_amount, position.base_token)
-                .await
-                .context("quote current value")?;
-
-            position.last_value_usd = format_amount(base_amount, base_decimals) * base_price;
-            position.last_updated_at = OffsetDateTime::now_utc();
-        }
-        Ok(())
+    /// Clones of the open positions, so they can be priced and traded
+    /// without holding the portfolio lock.
+    pub fn open_positions(&self) -> Vec<Position> {
+        self.positions.values().cloned().collect()
     }
 
-    pub async fn generate_exit_orders(
-        &self,
-        trader: &Trader,
-        config: &BotConfig,
-    ) -> Result<Vec<ExitOrder>> {
-        let mut orders = vec![];
-        let mut base_price_cache: HashMap<Address, f64> = HashMap::new();
-        let mut base_decimal_cache: HashMap<Address, u8> = HashMap::new();
-
-        for position in self.positions.values() {
-            let base_price = if let Some(price) = base_price_cache.get(&position.base_token) {
-                *price
-            } else {
-                let price = trader.fetch_base_usd_price(position.base_token).await?;
-                base_price_cache.insert(position.base_token, price);
-                price
-            };
-
-            let base_decimals = if let Some(decimals) = base_decimal_cache.get(&position.base_token)
-            {
-                *decimals
-            } else {
-                let decimals = trader
-                    .token_decimals(position.base_token)
-                    .await
-                    .unwrap_or(position.base_token_decimals);
-                base_decimal_cache.insert(position.base_token, decimals);
-                decimals
-            };
-
-            let base_amount = trader
-                .quote_sell(&position.token, position.token_amount, position.base_token)
-                .await?;
-
-            let current_value_usd = format_amount(base_amount, base_decimals) * base_price;
-            let entry_value = position.entry_value_usd();
-
-            if entry_value <= 0.0 {
-                continue;
-            }
-
-            let pnl_bps = ((current_value_usd / entry_value) - 1.0) * 10_000.0;
-
-            let slippage = config.slippage_bps() as u64;
-            let min_output = base_amount * U256::from(10_000 - slippage) / U256::from(10_000);
-
-            if pnl_bps >= position.take_profit_bps as f64 {
-                orders.push(ExitOrder {
-                    position_id: position.id,
-                    token_address: position.token,
-                    base_token: position.base_token,
-                    token_amount: position.token_amount,
-                    min_output,
-                    reason: ExitReason::TakeProfit,
-                });
-                continue;
-            }
+    pub fn position(&self, id: &Uuid) -> Option<Position> {
+        self.positions.get(id).cloned()
+    }
 
-            if pnl_bps <= -(position.stop_loss_bps as f64) {
-                orders.push(ExitOrder {
-                    position_id: position.id,
-                    token_address: position.token,
-                    base_token: position.base_token,
-                    token_amount: position.token_amount,
-                    min_output,
-                    reason: ExitReason::StopLoss,
-                });
+    /// Stores fresh marks. Positions closed since they were priced are
+    /// skipped.
+    pub fn record_valuations(&mut self, valuations: &[Valuation]) {
+        for valuation in valuations {
+            if let Some(position) = self.positions.get_mut(&valuation.position_id) {
+                position.last_value_usd = valuation.value_usd;
+                position.last_updated_at = valuation.priced_at;
             }
         }
-
-        Ok(orders)
     }
 
-    pub fn close_position(&mut self, id: Uuid) -> Result<()> {
-        self.positions
-            .remove(&id)
+    /// Books a filled exit. Realized PnL is what `execution` redeemed at
+    /// `base_price_usd`, or the last mark when the base price is unknown.
+    /// Partial exits shrink the position and its cost basis
+    /// proportionally; full exits close it.
+    pub fn settle_exit(
+        &mut self,
+        exit: &ExitOrder,
+        execution: &ExecutionResult,
+        base_price_usd: Option<f64>,
+    ) -> Result<()> {
+        let position = self
+            .positions
+            .get_mut(&exit.position_id)
             .context("position not found for closing")?;
+
+        let sold = if exit.token_amount >= position.token_amount || position.token_amount.is_zero()
+        {
+            1.0
+        } else {
+            format_amount(exit.token_amount, 0) / format_amount(position.token_amount, 0)
+        };
+        let proceeds_usd = base_price_usd.map_or(position.last_value_usd * sold, |price| {
+            format_amount(execution.base_spent, position.base_token_decimals) * price
+        });
+        *self
+            .realized_pnl_usd
+            .entry(position.strategy.clone())
+            .or_default() += proceeds_usd - position.entry_value_usd() * sold;
+
+        if exit.token_amount >= position.token_amount {
+            self.positions.remove(&exit.position_id);
+            return Ok(());
+        }
+
+        let remaining = position.token_amount - exit.token_amount;
+        position.base_spent = position.base_spent * remaining / position.token_amount;
+        position.last_value_usd *= 1.0 - sold;
+        position.token_amount = remaining;
         Ok(())
     }
 
-    pub fn snapshot(&self) -> PortfolioSnapshot {
+    pub fn snapshot(&self, strategies: &[&str]) -> PortfolioSnapshot {
         let mut total_value_usd = 0.0;
         let mut positions = Vec::with_capacity(self.positions.len());
         for position in self.positions.values() {
@@ -255,6 +278,8 @@ impl Portfolio {
 
             positions.push(PositionSnapshot {
                 id: position.id,
+                wallet: position.wallet,
+                strategy: position.strategy.clone(),
                 token: position.token,
                 base_token: position.base_token,
                 token_symbol: position.token_symbol.clone(),
@@ -271,9 +296,203 @@ impl Portfolio {
         PortfolioSnapshot {
             total_positions: positions.len(),
             total_value_usd,
+            strategies: self.strategy_snapshots(strategies),
             positions,
         }
     }
+
+    /// PnL per strategy id, covering `configured` and every strategy with
+    /// open positions or realized trades.
+    pub fn strategy_snapshots(&self, configured: &[&str]) -> Vec<StrategySnapshot> {
+        let mut strategies: BTreeMap<&str, StrategySnapshot> = configured
+            .iter()
+            .map(|id| (*id, StrategySnapshot::new(id)))
+            .collect();
+        for (id, realized) in &self.realized_pnl_usd {
+            strategies
+                .entry(id)
+                .or_insert_with(|| StrategySnapshot::new(id))
+                .realized_pnl_usd = *realized;
+        }
+        for position in self.positions.values() {
+            let snapshot = strategies
+                .entry(&position.strategy)
+                .or_insert_with(|| StrategySnapshot::new(&position.strategy));
+            snapshot.open_positions += 1;
+            snapshot.entry_value_usd += position.entry_value_usd();
+            snapshot.current_value_usd += position.last_value_usd;
+        }
+        strategies
+            .into_values()
+            .map(|mut snapshot| {
+                snapshot.unrealized_pnl_usd = snapshot.current_value_usd - snapshot.entry_value_usd;
+                snapshot.total_pnl_usd = snapshot.realized_pnl_usd + snapshot.unrealized_pnl_usd;
+                snapshot
+            })
+            .collect()
+    }
+
+    /// Open positions and their marked value for one trading wallet. The
+    /// primary wallet also owns positions opened before wallet rotation.
+    pub fn wallet_snapshot(
+        &self,
+        wallet: Address,
+        is_primary: bool,
+        native_balance: f64,
+    ) -> WalletSnapshot {
+        let owned = self
+            .positions
+            .values()
+            .filter(|p| p.wallet == wallet || (is_primary && p.wallet.is_zero()))
+            .collect::<Vec<_>>();
+
+        WalletSnapshot {
+            address: wallet,
+            native_balance,
+            open_positions: owned.len(),
+            positions_value_usd: owned.iter().map(|p| p.last_value_usd).sum(),
+            position_ids: owned.iter().map(|p| p.id).collect(),
+        }
+    }
+}
+
+/// Mark-to-market of one position.
+#[derive(Debug, Clone)]
+pub struct Valuation {
+    pub position_id: Uuid,
+    /// Base token received for selling the whole position right now.
+    pub base_amount: U256,
+    pub value_usd: f64,
+    pub priced_at: OffsetDateTime,
+}
+
+/// Quotes every position against its pool and prices the proceeds in USD.
+pub async fn value_positions(positions: &[Position], trader: &Trader) -> Result<Vec<Valuation>> {
+    let mut base_price_cache: HashMap<Address, f64> = HashMap::new();
+    let mut base_decimal_cache: HashMap<Address, u8> = HashMap::new();
+    let mut valuations = Vec::with_capacity(positions.len());
+
+    for position in positions {
+        let base_price = if let Some(price) = base_price_cache.get(&position.base_token) {
+            *price
+        } else {
+            let price = trader
+                .fetch_base_usd_price(position.base_token)
+                .await
+                .context("fetch base usd price")?;
+            base_price_cache.insert(position.base_token, price);
+            price
+        };
+
+        let base_decimals = if let Some(decimals) = base_decimal_cache.get(&position.base_token) {
+            *decimals
+        } else {
+            let decimals = trader
+                .token_decimals(position.base_token)
+                .await
+                .unwrap_or(position.base_token_decimals);
+            base_decimal_cache.insert(position.base_token, decimals);
+            decimals
+        };
+
+        let base_amount = trader
+            .quote_sell(
+                &position.token,
+                position.token_amount,
+                position.base_token,
+                position.route,
+            )
+            .await
+            .context("quote current value")?;
+
+        valuations.push(Valuation {
+            position_id: position.id,
+            base_amount,
+            value_usd: format_amount(base_amount, base_decimals) * base_price,
+            priced_at: OffsetDateTime::now_utc(),
+        });
+    }
+    Ok(valuations)
+}
+
+/// Sizes a full exit of `position` to the price impact limit. `None` when
+/// even a downsized exit would move the pool too far.
+pub async fn plan_exit(
+    position: &Position,
+    reason: ExitReason,
+    base_amount: U256,
+    trader: &Trader,
+    config: &BotConfig,
+) -> Result<Option<ExitOrder>> {
+    let sized = trader
+        .fit_price_impact(
+            position.pair_address,
+            position.token,
+            position.base_token,
+            position.token_amount,
+            position.route,
+        )
+        .await?;
+    let sized = match sized {
+        Some(sized) => sized,
+        // Holding through a stop-loss or a rug costs more than the impact.
+        None if reason.is_protective() => {
+            let impact_bps = trader
+                .price_impact_bps(
+                    position.pair_address,
+                    position.token,
+                    position.base_token,
+                    position.token_amount,
+                    position.route,
+                )
+                .await?;
+            warn!(
+                position = ?position.id,
+                reason = ?reason,
+                impact_bps,
+                "exit price impact above limit, selling anyway"
+            );
+            ImpactCheck {
+                amount_in: position.token_amount,
+                impact_bps,
+            }
+        }
+        None => {
+            warn!(
+                position = ?position.id,
+                reason = ?reason,
+                "exit price impact above limit, holding position"
+            );
+            return Ok(None);
+        }
+    };
+
+    let expected_out = if sized.amount_in == position.token_amount {
+        base_amount
+    } else {
+        trader
+            .quote_sell(
+                &position.token,
+                sized.amount_in,
+                position.base_token,
+                position.route,
+            )
+            .await?
+    };
+    let slippage = config.slippage_bps() as u64;
+    let min_output = expected_out * U256::from(10_000 - slippage) / U256::from(10_000);
+
+    Ok(Some(ExitOrder {
+        position_id: position.id,
+        wallet: position.wallet,
+        token_address: position.token,
+        base_token: position.base_token,
+        token_amount: sized.amount_in,
+        min_output,
+        route: position.route,
+        price_impact_bps: sized.impact_bps,
+        reason,
+    }))
 }
 
 fn format_amount(amount: U256, decimals: u8) -> f64 {
@@ -287,12 +506,40 @@ fn format_amount(amount: U256, decimals: u8) -> f64 {
 pub struct PortfolioSnapshot {
     pub total_positions: usize,
     pub total_value_usd: f64,
+    pub strategies: Vec<StrategySnapshot>,
     pub positions: Vec<PositionSnapshot>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct StrategySnapshot {
+    pub id: String,
+    pub open_positions: usize,
+    pub entry_value_usd: f64,
+    pub current_value_usd: f64,
+    pub realized_pnl_usd: f64,
+    pub unrealized_pnl_usd: f64,
+    pub total_pnl_usd: f64,
+}
+
+impl StrategySnapshot {
+    fn new(id: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            open_positions: 0,
+            entry_value_usd: 0.0,
+            current_value_usd: 0.0,
+            realized_pnl_usd: 0.0,
+            unrealized_pnl_usd: 0.0,
+            total_pnl_usd: 0.0,
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct PositionSnapshot {
     pub id: Uuid,
+    pub wallet: Address,
+    pub strategy: String,
     pub token: Address,
     pub base_token: Address,
     pub token_symbol: String,
@@ -304,3 +551,12 @@ pub struct PositionSnapshot {
     pub last_updated_at: OffsetDateTime,
     pub entry_tx: String,
 }
+
+#[derive(Debug, Serialize)]
+pub struct WalletSnapshot {
+    pub address: Address,
+    pub native_balance: f64,
+    pub open_positions: usize,
+    pub positions_value_usd: f64,
+    pub position_ids: Vec<Uuid>,
+}
@@ -0,0 +1,201 @@
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+};
+
+use anyhow::Result;
+use ethers::types::Address;
+use time::{Duration, OffsetDateTime};
+use tokio::time::{MissedTickBehavior, interval};
+use tracing::{error, info, warn};
+
+use crate::config::BotConfig;
+
+use super::{
+    dataset::{MarketSnapshot, RecordedQuote, SnapshotWriter},
+    http::HttpClient,
+    portfolio::Portfolio,
+    risk::RiskAnalyzer,
+    scanner::{DexScreenerScanner, GemCandidate},
+    trader::Trader,
+};
+
+/// Captures the raw API responses the strategy consumes so discovery and
+/// risk scoring can later be replayed offline.
+///
+/// A pair that passes the discovery filters stays tracked for `track_for`
+/// after it was last listed, and pairs of open positions are always
+/// tracked; their token responses are recorded whenever the pair drops out
+/// of the discovery listings so replays keep pricing them.
+pub struct Recorder {
+    config: BotConfig,
+    scanner: DexScreenerScanner,
+    risk: RiskAnalyzer,
+    /// Only set when router quotes are recorded, which needs a signer.
+    trader: Option<Trader>,
+    track_for: Duration,
+    /// Pair address to token and the last time discovery listed it.
+    tracked: HashMap<Address, (Address, OffsetDateTime)>,
+}
+
+impl Recorder {
+    pub async fn new(config: BotConfig, with_quotes: bool, track_for: Duration) -> Result<Self> {
+        let http = HttpClient::new(&config.http);
+        let trader = if with_quotes {
+            Some(Trader::new(config.clone(), http.clone()).await?)
+        } else {
+            None
+        };
+        Ok(Self {
+            scanner: DexScreenerScanner::new(http.clone()),
+            risk: RiskAnalyzer::new(config.clone(), http)?,
+            config,
+            trader,
+            track_for,
+            tracked: HashMap::new(),
+        })
+    }
+
+    /// Captures a snapshot every `every` until `shutdown` resolves. A failed
+    /// round is logged and skipped.
+    pub async fn run(
+        &mut self,
+        writer: &mut SnapshotWriter,
+        every: std::time::Duration,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<()> {
+        let mut ticker = interval(every);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        tokio::pin!(shutdown);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = &mut shutdown => return Ok(()),
+            }
+            let snapshot = match self.capture().await {
+                Ok(snapshot) => snapshot,
+                Err(err) => {
+                    error!(error = ?err, "market snapshot failed");
+                    continue;
+                }
+            };
+            let file = writer.append(&snapshot)?;
+            info!(
+                file = ?file,
+                pairs = snapshot.pairs.len(),
+                tracked_pairs = snapshot.tracked_pairs.len(),
+                security = snapshot.security.len(),
+                quotes = snapshot.quotes.len(),
+                "recorded market snapshot"
+            );
+        }
+    }
+
+    pub async fn capture(&mut self) -> Result<MarketSnapshot> {
+        let captured_at = OffsetDateTime::now_utc();
+        let pairs = self.scanner.fetch_discovery_pairs(&self.config).await?;
+        let candidates = self.scanner.candidates_from_pairs(&pairs, &self.config)?;
+        let listed = candidates
+            .iter()
+            .map(|candidate| candidate.pair_address)
+            .collect::<HashSet<_>>();
+        let selected = self
+            .scanner
+            .select_candidates(candidates, &self.config, captured_at);
+
+        for candidate in &selected {
+            self.tracked.insert(
+                candidate.pair_address,
+                (candidate.token_address, captured_at),
+            );
+        }
+        self.tracked
+            .retain(|_, (_, last_listed)| captured_at - *last_listed < self.track_for);
+
+        let held = Portfolio::load()
+            .unwrap_or_default()
+            .active_positions()
+            .into_iter()
+            .map(|position| (position.pair_address, position.token))
+            .collect::<Vec<_>>();
+        let unlisted_tokens = self
+            .tracked
+            .iter()
+            .map(|(pair, (token, _))| (*pair, *token))
+            .chain(held)
+            .filter(|(pair, _)| !listed.contains(pair))
+            .map(|(_, token)| token)
+            .collect::<HashSet<_>>();
+
+        let mut tracked_pairs = Vec::new();
+        for token in unlisted_tokens {
+            match self.scanner.fetch_token_pairs(&token, &self.config).await {
+                Ok(pairs) => tracked_pairs.extend(pairs),
+                Err(err) => warn!(token = ?token, error = ?err, "failed to record token pairs"),
+            }
+        }
+
+        let mut security = HashMap::new();
+        for candidate in &selected {
+            match self.risk.fetch_raw_security(candidate.token_address).await {
+                Ok(Some(raw)) => {
+                    security.insert(candidate.token_address, raw);
+                }
+                Ok(None) => {}
+                Err(err) => warn!(
+                    token = ?candidate.token_address,
+                    error = ?err,
+                    "failed to record security report"
+                ),
+            }
+        }
+
+        let mut quotes = Vec::new();
+        if let Some(trader) = &self.trader {
+            for candidate in &selected {
+                match quote_entry(trader, candidate, &self.config).await {
+                    Ok(quote) => quotes.push(quote),
+                    Err(err) => warn!(
+                        token = ?candidate.token_address,
+                        error = ?err,
+                        "failed to record router quote"
+                    ),
+                }
+            }
+        }
+
+        Ok(MarketSnapshot {
+            captured_at,
+            pairs,
+            tracked_pairs,
+            security,
+            quotes,
+        })
+    }
+}
+
+async fn quote_entry(
+    trader: &Trader,
+    candidate: &GemCandidate,
+    config: &BotConfig,
+) -> Result<RecordedQuote> {
+    let amount_in = config.position_size_wei()?;
+    let route = trader.resolve_route(candidate, amount_in).await?;
+    let router = route.router;
+    let amount_out = trader
+        .quote_buy(
+            &candidate.token_address,
+            amount_in,
+            candidate.base_token,
+            route,
+        )
+        .await?;
+    Ok(RecordedQuote {
+        pair_address: candidate.pair_address,
+        token: candidate.token_address,
+        base_token: candidate.base_token,
+        router,
+        amount_in,
+        amount_out,
+    })
+}
@@ -0,0 +1,251 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow};
+use ethers::{
+    signers::Signer,
+    types::{Bytes, U64},
+    utils::keccak256,
+};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tracing::debug;
+
+use crate::config::{PrivateRelayConfig, RelayMethod};
+
+/// JSON-RPC client for a private transaction relay or bundle builder.
+pub struct PrivateRelay {
+    config: PrivateRelayConfig,
+    http: Client,
+}
+
+impl PrivateRelay {
+    pub fn new(config: PrivateRelayConfig) -> Self {
+        Self {
+            config,
+            http: Client::builder()
+                .timeout(Duration::from_secs(10))
+                .user_agent("agentic-memecoin-bot/1.0")
+                .build()
+                .expect("reqwest client build"),
+        }
+    }
+
+    pub fn max_blocks(&self) -> u64 {
+        self.config.max_blocks
+    }
+
+    pub fn fallback_to_public(&self) -> bool {
+        self.config.fallback_to_public
+    }
+
+    /// Hands a signed transaction to the relay, valid until `max_blocks` past
+    /// `current_block`. Bundle relays get one single-transaction bundle per
+    /// target block.
+    pub async fn submit<S: Signer>(
+        &self,
+        raw_tx: &Bytes,
+        current_block: U64,
+        signer: &S,
+    ) -> Result<()> {
+        let last_block = current_block + self.config.max_blocks;
+        match self.config.method {
+            RelayMethod::PrivateTransaction => {
+                self.call(
+                    "eth_sendPrivateTransaction",
+                    json!([{ "tx": raw_tx, "maxBlockNumber": last_block }]),
+                    signer,
+                )
+                .await?;
+            }
+            RelayMethod::Bundle => {
+                for offset in 1..=self.config.max_blocks {
+                    self.call(
+                        "eth_sendBundle",
+                        json!([{ "txs": [raw_tx], "blockNumber": current_block + offset }]),
+                        signer,
+                    )
+                    .await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn call<S: Signer>(&self, method: &str, params: Value, signer: &S) -> Result<Value> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        })
+        .to_string();
+
+        // Flashbots-style authentication: the relay recovers our address from a
+        // personal signature over the body hash.
+        let digest = format!("0x{}", hex::encode(keccak256(body.as_bytes())));
+        let signature = signer
+            .sign_message(digest)
+            .await
+            .map_err(|err| anyhow!("sign relay request: {err}"))?;
+
+        let resp: RelayResponse = self
+            .http
+            .post(&self.config.url)
+            .header("content-type", "application/json")
+            .header(
+                "X-Flashbots-Signature",
+                format!("{:?}:0x{signature}", signer.address()),
+            )
+            .body(body)
+            .send()
+            .await
+            .with_context(|| format!("{method} request to private relay"))?
+            .error_for_status()?
+            .json()
+            .await
+            .with_context(|| format!("decode {method} response"))?;
+
+        if let Some(error) = resp.error {
+            return Err(anyhow!(
+                "private relay rejected {method}: {} ({})",
+                error.message,
+                error.code
+            ));
+        }
+        debug!(method, result = ?resp.result, "private relay accepted transaction");
+        Ok(resp.result.unwrap_or(Value::Null))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RelayResponse {
+    #[serde(default)]
+    result: Option<Value>,
+    #[serde(default)]
+    error: Option<RelayError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RelayError {
+    code: i64,
+    message: String,
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use axum::{Json, Router, extract::State, http::HeaderMap, routing::post};
+    use ethers::{
+        signers::LocalWallet,
+        types::{Address, Signature},
+    };
+
+    use super::*;
+
+    /// Anvil's first dev key.
+    pub(crate) const KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+    /// Requests accepted by the stand-in relay, with the address their
+    /// signature header recovered to.
+    pub(crate) type Received = Arc<Mutex<Vec<(Address, Value)>>>;
+
+    /// Stand-in relay that authenticates requests the way Flashbots does and
+    /// accepts every one that is signed by the address it names.
+    pub(crate) async fn stand_in_relay() -> (String, Received) {
+        let received = Received::default();
+        let app = Router::new()
+            .route("/", post(relay_request))
+            .with_state(received.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (url, received)
+    }
+
+    async fn relay_request(
+        State(received): State<Received>,
+        headers: HeaderMap,
+        body: String,
+    ) -> Json<Value> {
+        let digest = format!("0x{}", hex::encode(keccak256(body.as_bytes())));
+        let signer = headers
+            .get("X-Flashbots-Signature")
+            .and_then(|header| header.to_str().ok())
+            .and_then(|header| header.split_once(':'))
+            .and_then(|(address, signature)| {
+                let claimed = address.parse::<Address>().ok()?;
+                let recovered = signature.parse::<Signature>().ok()?.recover(digest).ok()?;
+                (recovered == claimed).then_some(recovered)
+            });
+        let Some(signer) = signer else {
+            return Json(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "error": { "code": -32600, "message": "invalid X-Flashbots-Signature" }
+            }));
+        };
+        let request = serde_json::from_str(&body).unwrap();
+        received.lock().unwrap().push((signer, request));
+        Json(json!({ "jsonrpc": "2.0", "id": 1, "result": { "bundleHash": "0x01" } }))
+    }
+
+    fn relay(url: String, method: RelayMethod) -> PrivateRelay {
+        PrivateRelay::new(PrivateRelayConfig {
+            url,
+            method,
+            max_blocks: 3,
+            fallback_to_public: false,
+        })
+    }
+
+    #[tokio::test]
+    async fn bundles_are_signed_and_sent_for_each_block_in_the_window() {
+        let (url, received) = stand_in_relay().await;
+        let wallet: LocalWallet = KEY.parse().unwrap();
+        let raw_tx = Bytes::from(vec![0x02, 0xf8]);
+
+        relay(url, RelayMethod::Bundle)
+            .submit(&raw_tx, U64::from(100), &wallet)
+            .await
+            .unwrap();
+
+        let received = received.lock().unwrap();
+        let blocks = received
+            .iter()
+            .map(|(signer, request)| {
+                assert_eq!(*signer, wallet.address());
+                assert_eq!(request["method"], "eth_sendBundle");
+                assert_eq!(request["params"][0]["txs"], json!([raw_tx]));
+                request["params"][0]["blockNumber"].clone()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            blocks,
+            [101u64, 102, 103].map(|block| json!(U64::from(block)))
+        );
+    }
+
+    #[tokio::test]
+    async fn private_transactions_are_signed_and_bounded_by_the_window() {
+        let (url, received) = stand_in_relay().await;
+        let wallet: LocalWallet = KEY.parse().unwrap();
+        let raw_tx = Bytes::from(vec![0x02, 0xf8]);
+
+        relay(url, RelayMethod::PrivateTransaction)
+            .submit(&raw_tx, U64::from(100), &wallet)
+            .await
+            .unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        let (signer, request) = &received[0];
+        assert_eq!(*signer, wallet.address());
+        assert_eq!(request["method"], "eth_sendPrivateTransaction");
+        assert_eq!(
+            request["params"][0]["maxBlockNumber"],
+            json!(U64::from(103))
+        );
+    }
+}
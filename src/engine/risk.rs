@@ -2,37 +2,48 @@ use std::collections::BTreeMap;
 
 use anyhow::{Context, Result, anyhow};
 use ethers::types::Address;
-use reqwest::Client;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use time::OffsetDateTime;
 
-use crate::config::{BotConfig, RiskHeuristicsConfig};
+use crate::config::BotConfig;
 
-use super::scanner::GemCandidate;
+use super::{
+    http::HttpClient,
+    policy::{RiskPolicy, RuleOutcome},
+    scanner::GemCandidate,
+};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct TokenRiskReport {
+    pub token: Address,
+    pub pair_address: Address,
     pub score: f64,
+    /// Score the token needed to pass.
+    pub pass_score: f64,
     pub is_safe: bool,
     pub flags: Vec<String>,
+    /// Per-rule breakdown of `score`, in policy order.
+    pub rules: Vec<RuleOutcome>,
     pub raw_security: Option<GoPlusTokenSecurity>,
+    #[serde(with = "time::serde::rfc3339")]
     pub evaluated_at: OffsetDateTime,
 }
 
 pub struct RiskAnalyzer {
     config: BotConfig,
-    client: Client,
+    policy: RiskPolicy,
+    http: HttpClient,
 }
 
 impl RiskAnalyzer {
-    pub fn new(config: BotConfig) -> Self {
-        Self {
+    /// Fails when the risk policy cannot be read or is invalid.
+    pub fn new(config: BotConfig, http: HttpClient) -> Result<Self> {
+        Ok(Self {
+            policy: RiskPolicy::load(config.risk.policy_file.as_deref())?,
             config,
-            client: Client::builder()
-                .user_agent("agentic-memecoin-bot/1.0")
-                .build()
-                .expect("reqwest client build"),
-        }
+            http,
+        })
     }
 
     pub async fn evaluate_candidate(&self, candidate: &GemCandidate) -> Result<TokenRiskReport> {
@@ -40,50 +51,28 @@ impl RiskAnalyzer {
             .fetch_security_report(candidate.token_address)
             .await
             .context("fetch security report")?;
+        self.assess(candidate, security)
+    }
 
-        let mut score = 0.0;
-        let mut flags = Vec::new();
-
-        if candidate.liquidity_usd >= self.config.strategy.min_liquidity_usd {
-            score += 1.0;
-        } else {
-            flags.push("liquidity-below-threshold".into());
-        }
-
-        if candidate.volume24h_usd >= self.config.strategy.min_daily_volume_usd {
-            score += 0.8;
-        } else {
-            flags.push("volume-24h-low".into());
-        }
-
-        if candidate.locked_liquidity_ratio.unwrap_or(0.0) >= self.config.risk.min_lock_ratio {
-            score += 1.2;
-        } else {
-            flags.push("insufficient-liquidity-lock".into());
-        }
-
-        if candidate
-            .holder_count
-            .unwrap_or(self.config.risk.min_holder_count)
-            >= self.config.risk.min_holder_count
-        {
-            score += 0.7;
-        } else {
-            flags.push("holder-count-low".into());
-        }
-
-        let (security_score, mut security_flags) =
-            evaluate_security_policy(&security, candidate, &self.config.risk)?;
-
-        score += security_score;
-        flags.append(&mut security_flags);
-
-        let is_safe = score >= 2.8 && !flags.iter().any(|flag| flag.starts_with("critical"));
+    /// Scores `candidate` against an already fetched (or recorded) GoPlus
+    /// report.
+    pub fn assess(
+        &self,
+        candidate: &GemCandidate,
+        security: Option<GoPlusTokenSecurity>,
+    ) -> Result<TokenRiskReport> {
+        let verdict = self
+            .policy
+            .evaluate(candidate, security.as_ref(), &self.config);
 
         Ok(TokenRiskReport {
-            score,
-            is_safe,
-            flags,
+            token: candidate.token_address,
+            pair_address: candidate.pair_address,
+            score: verdict.score,
+            pass_score: verdict.pass_score,
+            is_safe: verdict.is_safe,
+            flags: verdict.flags,
+            rules: verdict.rules,
             raw_security: security,
             evaluated_at: OffsetDateTime::now_utc(),
         })
@@ -93,94 +82,38 @@ impl RiskAnalyzer {
         &self,
         token_address: Address,
     ) -> Result<Option<GoPlusTokenSecurity>> {
+        self.fetch_raw_security(token_address)
+            .await?
+            .map(|raw| GoPlusTokenSecurity::deserialize(raw).context("decode goplus report"))
+            .transpose()
+    }
+
+    /// The raw GoPlus `token_security` entry for `token_address`, if GoPlus
+    /// knows the token. Reports are reused for `SECURITY_CACHE_SECS`.
+    pub async fn fetch_raw_security(&self, token_address: Address) -> Result<Option<Value>> {
         let chain_id = self.config.chain as u64;
+        let token = format!("{token_address:?}");
+        let cache_key = format!("goplus:{chain_id}:{token}");
+        if let Some(raw) = self.http.cached(&cache_key) {
+            return Ok(Some(raw));
+        }
+
         let url = format!(
-            "https://api.gopluslabs.io/api/v1/token_security/{chain_id}?contract_addresses={token}",
-            chain_id = chain_id,
-            token = format!("{token_address:?}")
+            "{}/api/v1/token_security/{chain_id}?contract_addresses={token}",
+            self.config.api.goplus_url
         );
-
-        let resp: GoPlusResponse = self
-            .client
-            .get(url)
-            .send()
-            .await?
-            .error_for_status()?
-            .json()
-            .await?;
-
+        let resp: GoPlusResponse = self.http.get_json(&url).await?;
         if resp.code != 1 {
             return Err(anyhow!("goplus api error: {}", resp.message));
         }
 
-        Ok(resp
-            .result
-            .and_then(|mut map| map.remove(&format!("{token_address:?}"))))
-    }
-}
-
-fn evaluate_security_policy(
-    security: &Option<GoPlusTokenSecurity>,
-    candidate: &GemCandidate,
-    risk: &RiskHeuristicsConfig,
-) -> Result<(f64, Vec<String>)> {
-    let mut score = 0.0;
-    let mut flags = Vec::new();
-
-    let Some(security) = security else {
-        flags.push("critical:goplus-missing".into());
-        return Ok((score, flags));
-    };
-
-    if security.is_honeypot() {
-        flags.push("critical:honeypot-detected".into());
-    } else {
-        score += 1.0;
-    }
-
-    if security.trading_disabled() {
-        flags.push("critical:trading-disabled".into());
-    }
-
-    if security.can_take_back_ownership() {
-        flags.push("critical:owner-can-revoke".into());
-    } else {
-        score += 0.4;
-    }
-
-    if security.is_proxy() {
-        flags.push("critical:proxy-contract".into());
-    } else {
-        score += 0.3;
-    }
-
-    let total_tax = security
-        .buy_tax()
-        .unwrap_or_default()
-        .max(security.sell_tax().unwrap_or_default());
-    if total_tax <= 15.0 {
-        score += 0.4;
-    } else {
-        flags.push(format!("critical:excessive-tax:{total_tax}"));
-    }
-
-    if let Some(top_holder) = security.top10_holder_percent() {
-        if top_holder <= risk.max_top_holder_percent {
-            score += 0.5;
-        } else {
-            flags.push(format!("critical:top-holders:{top_holder:.2}"));
-        }
-    }
-
-    if let Some(renounced) = candidate.contract_renounced_score {
-        if renounced >= risk.min_renounced_score {
-            score += 0.2;
-        } else {
-            flags.push(format!("renounce-score-low:{renounced}"));
+        // Unknown tokens are not cached: GoPlus may index them shortly.
+        let raw = resp.result.and_then(|mut map| map.remove(&token));
+        if let Some(raw) = &raw {
+            self.http.store(&cache_key, raw, self.http.security_ttl());
         }
+        Ok(raw)
     }
-
-    Ok((score, flags))
 }
 
 #[derive(Debug, Deserialize)]
@@ -188,10 +121,10 @@ struct GoPlusResponse {
     code: i64,
     message: String,
     #[serde(default)]
-    result: Option<BTreeMap<String, GoPlusTokenSecurity>>,
+    result: Option<BTreeMap<String, Value>>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GoPlusTokenSecurity {
     #[serde(default)]
     pub is_honeypot: Option<String>,
@@ -230,40 +163,74 @@ pub struct GoPlusTokenSecurity {
 }
 
 impl GoPlusTokenSecurity {
-    fn is_honeypot(&self) -> bool {
+    pub(super) fn is_honeypot(&self) -> bool {
         self.is_honeypot
             .as_deref()
             .map(|v| v == "1")
             .unwrap_or(false)
     }
 
-    fn trading_disabled(&self) -> bool {
+    pub(super) fn trading_disabled(&self) -> bool {
         self.trading_disabled
             .as_deref()
             .map(|v| v == "1")
             .unwrap_or(false)
     }
 
-    fn can_take_back_ownership(&self) -> bool {
+    pub(super) fn can_take_back_ownership(&self) -> bool {
         self.can_take_back_ownership
             .as_deref()
             .map(|v| v == "1")
             .unwrap_or(false)
     }
 
-    fn is_proxy(&self) -> bool {
+    pub(super) fn is_proxy(&self) -> bool {
         self.is_proxy.as_deref().map(|v| v == "1").unwrap_or(false)
     }
 
-    fn sell_tax(&self) -> Option<f64> {
+    pub(super) fn cannot_sell_all(&self) -> bool {
+        self.cannot_sell_all
+            .as_deref()
+            .map(|v| v == "1")
+            .unwrap_or(false)
+    }
+
+    pub(super) fn hidden_owner(&self) -> bool {
+        self.hidden_owner
+            .as_deref()
+            .map(|v| v == "1")
+            .unwrap_or(false)
+    }
+
+    pub(super) fn is_blacklisted(&self) -> bool {
+        self.is_blacklisted
+            .as_deref()
+            .map(|v| v == "1")
+            .unwrap_or(false)
+    }
+
+    pub(super) fn is_open_source(&self) -> bool {
+        self.is_open_source
+            .as_deref()
+            .map(|v| v == "1")
+            .unwrap_or(false)
+    }
+
+    pub(super) fn sell_tax(&self) -> Option<f64> {
         self.sell_tax.as_ref().and_then(|s| s.parse::<f64>().ok())
     }
 
-    fn buy_tax(&self) -> Option<f64> {
+    pub(super) fn buy_tax(&self) -> Option<f64> {
         self.buy_tax.as_ref().and_then(|s| s.parse::<f64>().ok())
     }
 
-    fn top10_holder_percent(&self) -> Option<f64> {
+    pub(super) fn holder_count(&self) -> Option<f64> {
+        self.holder_count
+            .as_ref()
+            .and_then(|s| s.parse::<f64>().ok())
+    }
+
+    pub(super) fn top10_holder_percent(&self) -> Option<f64> {
         self.top_holders.as_ref().and_then(|holders| {
             let sum: f64 = holders
                 .iter()
@@ -275,7 +242,7 @@ impl GoPlusTokenSecurity {
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GoPlusHolder {
     #[serde(default)]
     pub address: String,
@@ -285,7 +252,7 @@ pub struct GoPlusHolder {
     pub percent: String,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GoPlusLpHolder {
     #[serde(default)]
     pub address: String,
@@ -0,0 +1,437 @@
+use std::{
+    fmt,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use ethers::providers::{
+    Http, HttpClientError, JsonRpcClient, JsonRpcError, ProviderError, RpcError,
+};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use tokio::{task::JoinSet, time::sleep};
+use tracing::{debug, warn};
+
+use crate::config::RpcConfig;
+
+/// Consecutive transport failures after which an endpoint is benched.
+const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+const COOLDOWN: Duration = Duration::from_secs(30);
+/// Weight of the newest sample in the latency moving average.
+const LATENCY_SMOOTHING: f64 = 0.2;
+
+/// Reads answered by several endpoints when a quorum is configured.
+const QUORUM_METHODS: &[&str] = &["eth_getBalance", "eth_call"];
+/// Signed transactions are pushed to every endpoint at once.
+const BROADCAST_METHODS: &[&str] = &["eth_sendRawTransaction"];
+/// Never retried: a resend could duplicate the side effect.
+const NON_IDEMPOTENT_METHODS: &[&str] = &["eth_sendTransaction"];
+
+/// JSON-RPC transport spread over several HTTP endpoints. Reads go to the
+/// healthiest endpoint and fail over with backoff, critical reads can require
+/// agreement between endpoints, and raw transactions are broadcast to all.
+#[derive(Clone)]
+pub struct RpcPool {
+    inner: Arc<PoolInner>,
+}
+
+struct PoolInner {
+    endpoints: Vec<Endpoint>,
+    quorum: usize,
+    max_retries: u32,
+    retry_backoff: Duration,
+}
+
+struct Endpoint {
+    url: String,
+    transport: Http,
+    health: Mutex<EndpointHealth>,
+}
+
+#[derive(Debug, Default)]
+struct EndpointHealth {
+    consecutive_failures: u32,
+    total_requests: u64,
+    total_failures: u64,
+    latency_ms: Option<f64>,
+    benched_until: Option<Instant>,
+}
+
+/// Point-in-time health of one RPC endpoint.
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointStatus {
+    pub url: String,
+    pub healthy: bool,
+    pub consecutive_failures: u32,
+    pub total_requests: u64,
+    pub total_failures: u64,
+    pub latency_ms: Option<f64>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RpcPoolError {
+    #[error(transparent)]
+    Transport(#[from] HttpClientError),
+    #[error("{method}: only {agreeing} of {required} endpoints agreed")]
+    NoQuorum {
+        method: String,
+        agreeing: usize,
+        required: usize,
+    },
+    #[error("decode {method} response: {source}")]
+    Decode {
+        method: String,
+        source: serde_json::Error,
+    },
+}
+
+impl RpcError for RpcPoolError {
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            Self::Transport(err) => err.as_error_response(),
+            _ => None,
+        }
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        match self {
+            Self::Transport(err) => err.as_serde_error(),
+            Self::Decode { source, .. } => Some(source),
+            Self::NoQuorum { .. } => None,
+        }
+    }
+}
+
+impl From<RpcPoolError> for ProviderError {
+    fn from(err: RpcPoolError) -> Self {
+        ProviderError::JsonRpcClientError(Box::new(err))
+    }
+}
+
+impl fmt::Debug for RpcPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RpcPool")
+            .field(
+                "endpoints",
+                &self
+                    .inner
+                    .endpoints
+                    .iter()
+                    .map(|endpoint| &endpoint.url)
+                    .collect::<Vec<_>>(),
+            )
+            .field("quorum", &self.inner.quorum)
+            .finish()
+    }
+}
+
+impl RpcPool {
+    pub fn new(config: &RpcConfig) -> Result<Self> {
+        let endpoints = config
+            .http_urls
+            .iter()
+            .map(|url| {
+                Ok(Endpoint {
+                    url: url.clone(),
+                    transport: Http::from_str(url)
+                        .with_context(|| format!("invalid RPC endpoint {url}"))?,
+                    health: Mutex::new(EndpointHealth::default()),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        anyhow::ensure!(!endpoints.is_empty(), "no RPC endpoints configured");
+
+        Ok(Self {
+            inner: Arc::new(PoolInner {
+                quorum: config.quorum.clamp(1, endpoints.len()),
+                endpoints,
+                max_retries: config.max_retries,
+                retry_backoff: Duration::from_millis(config.retry_backoff_ms),
+            }),
+        })
+    }
+
+    pub fn endpoint_status(&self) -> Vec<EndpointStatus> {
+        let now = Instant::now();
+        self.inner
+            .endpoints
+            .iter()
+            .map(|endpoint| {
+                let health = endpoint.health.lock().expect("rpc health lock");
+                EndpointStatus {
+                    url: endpoint.url.clone(),
+                    healthy: health.benched_until.is_none_or(|until| until <= now),
+                    consecutive_failures: health.consecutive_failures,
+                    total_requests: health.total_requests,
+                    total_failures: health.total_failures,
+                    latency_ms: health.latency_ms,
+                }
+            })
+            .collect()
+    }
+
+    /// Endpoint indices, best first: endpoints off the bench before benched
+    /// ones, then fewest recent failures, then lowest latency.
+    fn ranked(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let mut ranked = self
+            .inner
+            .endpoints
+            .iter()
+            .enumerate()
+            .map(|(index, endpoint)| {
+                let health = endpoint.health.lock().expect("rpc health lock");
+                let benched = health.benched_until.is_some_and(|until| until > now);
+                (
+                    index,
+                    benched,
+                    health.consecutive_failures,
+                    health.latency_ms.unwrap_or(0.0),
+                )
+            })
+            .collect::<Vec<_>>();
+        ranked.sort_by(|a, b| (a.1, a.2).cmp(&(b.1, b.2)).then(a.3.total_cmp(&b.3)));
+        ranked.into_iter().map(|(index, ..)| index).collect()
+    }
+
+    async fn send(
+        &self,
+        index: usize,
+        method: &str,
+        params: &Value,
+    ) -> Result<Value, HttpClientError> {
+        send_to(&self.inner, index, method, params.clone()).await
+    }
+
+    /// Best endpoint first, failing over to the next one with exponential
+    /// backoff while the error is a transport failure rather than a JSON-RPC
+    /// error response.
+    async fn request_with_failover(
+        &self,
+        method: &str,
+        params: &Value,
+        retries: u32,
+    ) -> Result<Value, RpcPoolError> {
+        let ranked = self.ranked();
+        let mut attempt = 0;
+        loop {
+            let index = ranked[attempt as usize % ranked.len()];
+            match self.send(index, method, params).await {
+                Ok(value) => return Ok(value),
+                Err(err) if err.as_error_response().is_some() || attempt >= retries => {
+                    return Err(err.into());
+                }
+                Err(err) => {
+                    let backoff = self.inner.retry_backoff * 2u32.pow(attempt);
+                    debug!(
+                        method,
+                        endpoint = %self.inner.endpoints[index].url,
+                        error = %err,
+                        backoff_ms = backoff.as_millis() as u64,
+                        "rpc request failed, retrying"
+                    );
+                    sleep(backoff).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// Sends to every endpoint concurrently and returns the first answer at
+    /// least `required` endpoints agree on.
+    async fn request_from_all(
+        &self,
+        method: &str,
+        params: &Value,
+        required: usize,
+    ) -> Result<Value, RpcPoolError> {
+        let mut calls = JoinSet::new();
+        for index in 0..self.inner.endpoints.len() {
+            let inner = self.inner.clone();
+            let method = method.to_string();
+            let params = params.clone();
+            calls.spawn(async move { send_to(&inner, index, &method, params).await });
+        }
+
+        let mut tally: Vec<(Value, usize)> = Vec::new();
+        let mut last_error = None;
+        let mut best = 0;
+        while let Some(joined) = calls.join_next().await {
+            match joined.expect("rpc request task panicked") {
+                Ok(value) => {
+                    let count = match tally.iter_mut().find(|(seen, _)| *seen == value) {
+                        Some((_, count)) => {
+                            *count += 1;
+                            *count
+                        }
+                        None => {
+                            tally.push((value.clone(), 1));
+                            1
+                        }
+                    };
+                    best = best.max(count);
+                    if count >= required {
+                        // Let the stragglers finish: a broadcast should still
+                        // reach every endpoint and their health still counts.
+                        calls.detach_all();
+                        return Ok(value);
+                    }
+                }
+                Err(err) => last_error = Some(err),
+            }
+        }
+
+        match last_error {
+            Some(err) if tally.is_empty() => Err(err.into()),
+            _ => Err(RpcPoolError::NoQuorum {
+                method: method.to_string(),
+                agreeing: best,
+                required,
+            }),
+        }
+    }
+
+    /// Replaces a `latest` or `pending` block tag in a quorum read with the
+    /// newest block at least `quorum` endpoints have, so endpoints a block
+    /// apart still answer the same question.
+    async fn pin_to_quorum_block(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<Value, RpcPoolError> {
+        let Value::Array(mut args) = params else {
+            return Ok(params);
+        };
+        // `eth_call` and `eth_getBalance` both take the block as their second
+        // argument.
+        let floating = match args.get(1) {
+            None => true,
+            Some(Value::String(tag)) => tag == "latest" || tag == "pending",
+            Some(_) => false,
+        };
+        if !floating {
+            return Ok(Value::Array(args));
+        }
+        let block = self.quorum_block(method).await?;
+        args.truncate(1);
+        args.push(Value::String(format!("{block:#x}")));
+        Ok(Value::Array(args))
+    }
+
+    /// The `quorum`-th highest block number reported by the endpoints.
+    async fn quorum_block(&self, method: &str) -> Result<u64, RpcPoolError> {
+        let mut calls = JoinSet::new();
+        for index in 0..self.inner.endpoints.len() {
+            let inner = self.inner.clone();
+            calls.spawn(async move {
+                send_to(&inner, index, "eth_blockNumber", Value::Array(vec![])).await
+            });
+        }
+
+        let mut heights = Vec::new();
+        let mut last_error = None;
+        while let Some(joined) = calls.join_next().await {
+            match joined.expect("rpc request task panicked") {
+                Ok(Value::String(hex)) => {
+                    if let Ok(height) = u64::from_str_radix(hex.trim_start_matches("0x"), 16) {
+                        heights.push(height);
+                    }
+                }
+                Ok(_) => {}
+                Err(err) => last_error = Some(err),
+            }
+        }
+
+        heights.sort_unstable_by(|a, b| b.cmp(a));
+        match heights.get(self.inner.quorum - 1) {
+            Some(height) => Ok(*height),
+            None => match last_error {
+                Some(err) if heights.is_empty() => Err(err.into()),
+                _ => Err(RpcPoolError::NoQuorum {
+                    method: method.to_string(),
+                    agreeing: heights.len(),
+                    required: self.inner.quorum,
+                }),
+            },
+        }
+    }
+}
+
+async fn send_to(
+    inner: &PoolInner,
+    index: usize,
+    method: &str,
+    params: Value,
+) -> Result<Value, HttpClientError> {
+    let endpoint = &inner.endpoints[index];
+    let started = Instant::now();
+    let result = endpoint.transport.request::<_, Value>(method, params).await;
+
+    let mut health = endpoint.health.lock().expect("rpc health lock");
+    health.total_requests += 1;
+    match &result {
+        // An error response means the node is up and answered.
+        Ok(_) | Err(HttpClientError::JsonRpcError(_)) => {
+            let elapsed = started.elapsed().as_secs_f64() * 1000.0;
+            health.latency_ms = Some(match health.latency_ms {
+                Some(avg) => avg + LATENCY_SMOOTHING * (elapsed - avg),
+                None => elapsed,
+            });
+            health.consecutive_failures = 0;
+            health.benched_until = None;
+        }
+        Err(err) => {
+            health.total_failures += 1;
+            health.consecutive_failures += 1;
+            if health.consecutive_failures == MAX_CONSECUTIVE_FAILURES {
+                warn!(
+                    endpoint = %endpoint.url,
+                    error = %err,
+                    "rpc endpoint failing, benching for {}s",
+                    COOLDOWN.as_secs()
+                );
+            }
+            if health.consecutive_failures >= MAX_CONSECUTIVE_FAILURES {
+                health.benched_until = Some(Instant::now() + COOLDOWN);
+            }
+        }
+    }
+    result
+}
+
+#[async_trait]
+impl JsonRpcClient for RpcPool {
+    type Error = RpcPoolError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: fmt::Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let params = serde_json::to_value(params).map_err(|source| RpcPoolError::Decode {
+            method: method.to_string(),
+            source,
+        })?;
+
+        let value = if BROADCAST_METHODS.contains(&method) {
+            self.request_from_all(method, &params, 1).await?
+        } else if self.inner.quorum > 1 && QUORUM_METHODS.contains(&method) {
+            let params = self.pin_to_quorum_block(method, params).await?;
+            self.request_from_all(method, &params, self.inner.quorum)
+                .await?
+        } else if NON_IDEMPOTENT_METHODS.contains(&method) {
+            self.request_with_failover(method, &params, 0).await?
+        } else {
+            self.request_with_failover(method, &params, self.inner.max_retries)
+                .await?
+        };
+
+        serde_json::from_value(value).map_err(|source| RpcPoolError::Decode {
+            method: method.to_string(),
+            source,
+        })
+    }
+}
@@ -1,13 +1,15 @@
-use std::{cmp::Ordering, str::FromStr, time::Duration};
+use std::{cmp::Ordering, collections::HashMap, str::FromStr};
 
 use anyhow::{Context, Result, anyhow};
 use ethers::types::Address;
-use reqwest::Client;
 use serde::Deserialize;
 use time::{Duration as TimeDuration, OffsetDateTime};
-use tracing::instrument;
 
-use crate::config::{BotConfig, StrategyConfig};
+use crate::config::{
+    BotConfig, ExchangeConfig, MarketWindow, StrategyConfig, VenueConfig, VenueProtocol,
+};
+
+use super::http::HttpClient;
 
 #[derive(Debug, Clone)]
 pub struct GemCandidate {
@@ -19,53 +21,189 @@ pub struct GemCandidate {
     pub price_usd: f64,
     pub liquidity_usd: f64,
     pub volume24h_usd: f64,
+    pub volume_h6_usd: f64,
+    pub volume_h1_usd: f64,
     pub fdv_usd: f64,
     pub price_change_m5: f64,
     pub price_change_m15: f64,
     pub price_change_h1: f64,
+    pub price_change_h6: f64,
+    pub price_change_h24: f64,
+    /// Share of m5 transactions that were buys.
     pub buy_pressure_ratio: f64,
+    pub buy_pressure_m15: f64,
+    pub buy_pressure_h1: f64,
+    pub buy_pressure_h6: f64,
+    pub buy_pressure_h24: f64,
     pub holder_count: Option<u64>,
     pub locked_liquidity_ratio: Option<f64>,
     pub contract_renounced_score: Option<f64>,
     pub pair_created_at: OffsetDateTime,
     pub dex_id: String,
+    pub pool_labels: Vec<String>,
     pub confidence: f64,
     pub safety_flags: Vec<String>,
     pub usd_per_base: f64,
+    /// Other pools of the same token, deepest first, to route through when
+    /// this one has no usable venue.
+    pub alternate_pools: Vec<AlternatePool>,
+}
+
+/// A pool of a candidate's token that lost the dedup to a deeper or better
+/// supported one.
+#[derive(Debug, Clone)]
+pub struct AlternatePool {
+    pub pair_address: Address,
+    pub base_token: Address,
+    pub dex_id: String,
+    pub pool_labels: Vec<String>,
+    pub liquidity_usd: f64,
+    pub price_usd: f64,
+    pub usd_per_base: f64,
 }
 
-#[derive(Debug, Default)]
+impl GemCandidate {
+    /// This candidate traded through `pool` instead of its primary pool.
+    pub fn with_pool(&self, pool: &AlternatePool) -> GemCandidate {
+        GemCandidate {
+            pair_address: pool.pair_address,
+            base_token: pool.base_token,
+            dex_id: pool.dex_id.clone(),
+            pool_labels: pool.pool_labels.clone(),
+            liquidity_usd: pool.liquidity_usd,
+            price_usd: pool.price_usd,
+            usd_per_base: pool.usd_per_base,
+            alternate_pools: Vec::new(),
+            ..self.clone()
+        }
+    }
+
+    /// Whether a configured venue trades this pool's `dex_id` and protocol.
+    fn on_supported_venue(&self, venues: &[VenueConfig]) -> bool {
+        venues.iter().any(|venue| {
+            venue.dex_id.eq_ignore_ascii_case(&self.dex_id)
+                && (venue.protocol == VenueProtocol::UniswapV3) == self.is_concentrated_liquidity()
+        })
+    }
+
+    /// DexScreener labels Uniswap-V3-style pools with `v3` while keeping the
+    /// venue's plain `dex_id`.
+    pub fn is_concentrated_liquidity(&self) -> bool {
+        self.pool_labels
+            .iter()
+            .any(|label| label.eq_ignore_ascii_case("v3"))
+    }
+
+    /// Price change in percent over `window`.
+    pub fn price_change(&self, window: MarketWindow) -> f64 {
+        match window {
+            MarketWindow::M5 => self.price_change_m5,
+            MarketWindow::M15 => self.price_change_m15,
+            MarketWindow::H1 => self.price_change_h1,
+            MarketWindow::H6 => self.price_change_h6,
+            MarketWindow::H24 => self.price_change_h24,
+        }
+    }
+
+    /// Share of transactions over `window` that were buys.
+    pub fn buy_pressure(&self, window: MarketWindow) -> f64 {
+        match window {
+            MarketWindow::M5 => self.buy_pressure_ratio,
+            MarketWindow::M15 => self.buy_pressure_m15,
+            MarketWindow::H1 => self.buy_pressure_h1,
+            MarketWindow::H6 => self.buy_pressure_h6,
+            MarketWindow::H24 => self.buy_pressure_h24,
+        }
+    }
+
+    /// Last hour's volume relative to the hourly average of the last six
+    /// hours; `0` without h6 volume.
+    pub fn volume_acceleration(&self) -> f64 {
+        if self.volume_h6_usd > 0.0 {
+            self.volume_h1_usd / (self.volume_h6_usd / 6.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+#[derive(Default)]
 pub struct DexScreenerScanner {
-    client: Client,
+    http: HttpClient,
 }
 
 impl DexScreenerScanner {
+    pub fn new(http: HttpClient) -> Self {
+        Self { http }
+    }
+
     pub async fn discover_candidates(&self, config: &BotConfig) -> Result<Vec<GemCandidate>> {
+        let pairs = self.fetch_discovery_pairs(config).await?;
+        let candidates = self.candidates_from_pairs(&pairs, config)?;
+        Ok(self.select_candidates(candidates, config, OffsetDateTime::now_utc()))
+    }
+
+    /// Raw DexScreener pair objects from the trending and latest listings,
+    /// as discovery sees them before any conversion.
+    pub async fn fetch_discovery_pairs(
+        &self,
+        config: &BotConfig,
+    ) -> Result<Vec<serde_json::Value>> {
         let chain_key = chain_to_dexscreener_key(config.chain)
             .ok_or_else(|| anyhow!("chain not supported by DexScreener"))?;
 
+        let base_url = &config.api.dexscreener_url;
         let mut pairs = self
-            .fetch_trending_pairs(chain_key)
+            .fetch_trending_pairs(base_url, chain_key)
             .await
             .context("fetch_trending_pairs")?;
 
-        pairs.extend(self.fetch_latest_pairs(chain_key).await.unwrap_or_default());
+        pairs.extend(
+            self.fetch_latest_pairs(base_url, chain_key)
+                .await
+                .unwrap_or_default(),
+        );
+        Ok(pairs)
+    }
 
+    /// Converts raw (live or recorded) DexScreener pair objects without
+    /// filtering them.
+    pub fn candidates_from_pairs(
+        &self,
+        pairs: &[serde_json::Value],
+        config: &BotConfig,
+    ) -> Result<Vec<GemCandidate>> {
         let mut candidates = vec![];
         for pair in pairs {
+            let pair = DexScreenerPair::deserialize(pair).context("decode dexscreener pair")?;
             if let Some(candidate) = self
                 .to_candidate(pair, &config.strategy)
                 .context("convert pair to candidate")?
             {
-                if candidate.liquidity_usd >= config.strategy.min_liquidity_usd
-                    && candidate.volume24h_usd >= config.strategy.min_daily_volume_usd
-                    && (OffsetDateTime::now_utc() - candidate.pair_created_at)
-                        >= TimeDuration::minutes(config.strategy.min_age_minutes as i64)
-                {
-                    candidates.push(candidate);
-                }
+                candidates.push(candidate);
             }
         }
+        Ok(candidates)
+    }
+
+    /// Applies the liquidity, volume and age filters as of `now` and keeps
+    /// the most confident candidates.
+    pub fn select_candidates(
+        &self,
+        candidates: Vec<GemCandidate>,
+        config: &BotConfig,
+        now: OffsetDateTime,
+    ) -> Vec<GemCandidate> {
+        let candidates = candidates
+            .into_iter()
+            .filter(|candidate| {
+                candidate.liquidity_usd >= config.strategy.min_liquidity_usd
+                    && candidate.volume24h_usd >= config.strategy.min_daily_volume_usd
+                    && (now - candidate.pair_created_at)
+                        >= TimeDuration::minutes(config.strategy.min_age_minutes as i64)
+            })
+            .collect::<Vec<_>>();
+        let mut candidates = dedupe_by_token(candidates, &config.exchange);
 
         candidates.sort_by(|a, b| {
             b.confidence
@@ -73,7 +211,7 @@ impl DexScreenerScanner {
                 .unwrap_or(Ordering::Equal)
         });
         candidates.truncate(12);
-        Ok(candidates)
+        candidates
     }
 
     pub async fn fetch_token_candidates(
@@ -81,84 +219,73 @@ impl DexScreenerScanner {
         token: &Address,
         config: &BotConfig,
     ) -> Result<Vec<GemCandidate>> {
+        let pairs = self.fetch_token_pairs(token, config).await?;
+        self.candidates_from_pairs(&pairs, config)
+    }
+
+    /// Raw DexScreener pair objects for every pool of `token` on the
+    /// configured chain.
+    pub async fn fetch_token_pairs(
+        &self,
+        token: &Address,
+        config: &BotConfig,
+    ) -> Result<Vec<serde_json::Value>> {
         let chain_key = chain_to_dexscreener_key(config.chain)
             .ok_or_else(|| anyhow!("chain not supported by DexScreener"))?;
 
-        let pairs = self
-            .fetch_pairs_for_token(token)
+        let mut pairs = self
+            .fetch_pairs_for_token(&config.api.dexscreener_url, token)
             .await
             .context("fetch token pairs")?;
-
-        let mut candidates = Vec::new();
-        for pair in pairs {
-            if pair.chain_id != chain_key {
-                continue;
-            }
-            if let Some(candidate) = self.to_candidate(pair, &config.strategy)? {
-                candidates.push(candidate);
-            }
-        }
-        Ok(candidates)
+        pairs.retain(|pair| pair.get("chainId").and_then(|id| id.as_str()) == Some(chain_key));
+        Ok(pairs)
     }
 
-    #[instrument(skip(self, candidate, config))]
-    pub async fn has_momentum(&self, candidate: &GemCandidate, config: &BotConfig) -> Result<bool> {
-        let window = config.strategy.price_momentum_window_minutes as f64;
-        let score = (candidate.price_change_m5 * 0.4)
-            + (candidate.price_change_m15 * 0.35)
-            + (candidate.price_change_h1 * 0.25);
-        Ok(score >= 8.0 && candidate.buy_pressure_ratio >= 0.55 && window >= 5.0)
+    async fn fetch_trending_pairs(
+        &self,
+        base_url: &str,
+        chain_key: &str,
+    ) -> Result<Vec<serde_json::Value>> {
+        self.fetch_pairs(
+            &format!("dexscreener:trending:{chain_key}"),
+            &format!("{base_url}/latest/dex/trending/{chain_key}"),
+        )
+        .await
     }
 
-    async fn fetch_trending_pairs(&self, chain_key: &str) -> Result<Vec<DexScreenerPair>> {
-        let url = format!(
-            "https://api.dexscreener.com/latest/dex/trending/{chain}",
-            chain = chain_key
-        );
-        let resp: DexScreenerPairsResponse = self
-            .client
-            .get(url)
-            .timeout(Duration::from_secs(10))
-            .send()
-            .await?
-            .error_for_status()?
-            .json()
-            .await?;
-        Ok(resp.pairs.unwrap_or_default())
+    async fn fetch_latest_pairs(
+        &self,
+        base_url: &str,
+        chain_key: &str,
+    ) -> Result<Vec<serde_json::Value>> {
+        self.fetch_pairs(
+            &format!("dexscreener:latest:{chain_key}"),
+            &format!("{base_url}/latest/dex/pairs/{chain_key}"),
+        )
+        .await
     }
 
-    async fn fetch_latest_pairs(&self, chain_key: &str) -> Result<Vec<DexScreenerPair>> {
-        let url = format!(
-            "https://api.dexscreener.com/latest/dex/pairs/{chain}",
-            chain = chain_key
-        );
-        let resp: DexScreenerPairsResponse = self
-            .client
-            .get(url)
-            .timeout(Duration::from_secs(10))
-            .send()
-            .await?
-            .error_for_status()?
-            .json()
-            .await?;
-        Ok(resp.pairs.unwrap_or_default())
+    async fn fetch_pairs_for_token(
+        &self,
+        base_url: &str,
+        token: &Address,
+    ) -> Result<Vec<serde_json::Value>> {
+        self.fetch_pairs(
+            &format!("dexscreener:token:{token:?}"),
+            &format!("{base_url}/latest/dex/tokens/{token:?}"),
+        )
+        .await
     }
 
-    async fn fetch_pairs_for_token(&self, token: &Address) -> Result<Vec<DexScreenerPair>> {
-        let url = format!(
-            "https://api.dexscreener.com/latest/dex/tokens/{token}",
-            token = format!("{token:?}")
-        );
-        let resp: DexScreenerPairsResponse = self
-            .client
-            .get(url)
-            .timeout(Duration::from_secs(10))
-            .send()
-            .await?
-            .error_for_status()?
-            .json()
-            .await?;
-        Ok(resp.pairs.unwrap_or_default())
+    /// Pairs listed at `url`, reused for `MARKET_CACHE_SECS`.
+    async fn fetch_pairs(&self, cache_key: &str, url: &str) -> Result<Vec<serde_json::Value>> {
+        if let Some(pairs) = self.http.cached(cache_key) {
+            return Ok(pairs);
+        }
+        let resp: DexScreenerPairsResponse = self.http.get_json(url).await?;
+        let pairs = resp.pairs.unwrap_or_default();
+        self.http.store(cache_key, &pairs, self.http.market_ttl());
+        Ok(pairs)
     }
 
     fn to_candidate(
@@ -179,12 +306,6 @@ impl DexScreenerScanner {
         let confidence = compute_confidence_score(&pair);
         let safety_flags = collect_safety_flags(&pair);
 
-        let buy_pressure_ratio = {
-            let buys = pair.txns.m5.buys.max(1) as f64;
-            let sells = pair.txns.m5.sells.max(1) as f64;
-            buys / (buys + sells)
-        };
-
         let pair_created_at = pair
             .pair_created_at
             .and_then(|ms| OffsetDateTime::from_unix_timestamp(ms / 1000).ok())
@@ -206,23 +327,88 @@ impl DexScreenerScanner {
             price_usd: pair.price_usd.unwrap_or_default(),
             liquidity_usd: pair.liquidity.usd.unwrap_or_default(),
             volume24h_usd: pair.volume.h24.unwrap_or_default(),
+            volume_h6_usd: pair.volume.h6.unwrap_or_default(),
+            volume_h1_usd: pair.volume.h1.unwrap_or_default(),
             fdv_usd: pair.fdv.unwrap_or_default(),
             price_change_m5: pair.price_change.m5.unwrap_or_default(),
             price_change_m15: pair.price_change.m15.unwrap_or_default(),
             price_change_h1: pair.price_change.h1.unwrap_or_default(),
-            buy_pressure_ratio,
+            price_change_h6: pair.price_change.h6.unwrap_or_default(),
+            price_change_h24: pair.price_change.h24.unwrap_or_default(),
+            buy_pressure_ratio: pair.txns.m5.buy_pressure(),
+            buy_pressure_m15: pair.txns.m15.buy_pressure(),
+            buy_pressure_h1: pair.txns.h1.buy_pressure(),
+            buy_pressure_h6: pair.txns.h6.buy_pressure(),
+            buy_pressure_h24: pair.txns.h24.buy_pressure(),
             holder_count: pair.info.as_ref().and_then(|info| info.holders),
             locked_liquidity_ratio: pair.liquidity.locked,
             contract_renounced_score: pair.info.as_ref().and_then(|info| info.renounced),
             pair_created_at,
             dex_id: pair.dex_id,
+            pool_labels: pair.labels,
             confidence,
             safety_flags,
             usd_per_base,
+            alternate_pools: Vec::new(),
         }))
     }
 }
 
+/// Keeps one candidate per token: its deepest pool on a configured venue,
+/// or its deepest pool when none is on one. The other pools become the
+/// candidate's alternates. Tokens keep the order they were first seen in.
+///
+/// Entries pay in the native coin, so only pools quoted in one of
+/// `BASE_TOKENS` are considered when it is set; tokens without such a pool
+/// are dropped.
+fn dedupe_by_token(candidates: Vec<GemCandidate>, exchange: &ExchangeConfig) -> Vec<GemCandidate> {
+    let venues = &exchange.venues;
+    let mut index: HashMap<Address, usize> = HashMap::new();
+    let mut groups: Vec<Vec<GemCandidate>> = Vec::new();
+    for candidate in candidates {
+        if !exchange.base_tokens.is_empty() && !exchange.base_tokens.contains(&candidate.base_token)
+        {
+            continue;
+        }
+        let slot = *index.entry(candidate.token_address).or_insert_with(|| {
+            groups.push(Vec::new());
+            groups.len() - 1
+        });
+        // Trending and latest listings can both return the same pair.
+        if groups[slot]
+            .iter()
+            .all(|pool| pool.pair_address != candidate.pair_address)
+        {
+            groups[slot].push(candidate);
+        }
+    }
+
+    groups
+        .into_iter()
+        .filter_map(|mut pools| {
+            pools.sort_by(|a, b| {
+                b.on_supported_venue(venues)
+                    .cmp(&a.on_supported_venue(venues))
+                    .then(b.liquidity_usd.total_cmp(&a.liquidity_usd))
+            });
+            let mut pools = pools.into_iter();
+            let mut best = pools.next()?;
+            best.alternate_pools = pools
+                .map(|pool| AlternatePool {
+                    pair_address: pool.pair_address,
+                    base_token: pool.base_token,
+                    dex_id: pool.dex_id,
+                    pool_labels: pool.pool_labels,
+                    liquidity_usd: pool.liquidity_usd,
+                    price_usd: pool.price_usd,
+                    usd_per_base: pool.usd_per_base,
+                })
+                .collect();
+            Some(best)
+        })
+        .collect()
+}
+
 fn compute_confidence_score(pair: &DexScreenerPair) -> f64 {
     let liquidity = pair.liquidity.usd.unwrap_or(0.0).ln_1p();
     let volume = pair.volume.h24.unwrap_or(0.0).ln_1p();
@@ -265,15 +451,15 @@ fn chain_to_dexscreener_key(chain: ethers::types::Chain) -> Option<&'static str>
 #[derive(Debug, Deserialize)]
 struct DexScreenerPairsResponse {
     #[serde(default)]
-    pairs: Option<Vec<DexScreenerPair>>,
+    pairs: Option<Vec<serde_json::Value>>,
 }
 
 #[derive(Debug, Deserialize)]
 struct DexScreenerPair {
-    #[serde(rename = "chainId")]
-    chain_id: String,
     #[serde(rename = "dexId")]
     dex_id: String,
+    #[serde(default)]
+    labels: Vec<String>,
     #[serde(rename = "pairAddress")]
     pair_address: String,
     #[serde(rename = "baseToken")]
@@ -367,3 +553,11 @@ struct TransactionWindow {
     buys: u64,
     sells: u64,
 }
+
+impl TransactionWindow {
+    fn buy_pressure(&self) -> f64 {
+        let buys = self.buys.max(1) as f64;
+        let sells = self.sells.max(1) as f64;
+        buys / (buys + sells)
+    }
+}
@@ -0,0 +1,284 @@
+use std::{fs, time::Duration};
+
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use ethers::{
+    signers::{LocalWallet, MnemonicBuilder, Signer, WalletError, coins_bip39::English},
+    types::{
+        Address, Bytes, Signature, transaction::eip712::Eip712,
+        transaction::eip2718::TypedTransaction,
+    },
+    utils::rlp::Rlp,
+};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::config::SignerConfig;
+
+/// Transaction signer used by the trading middleware: either a key held in
+/// process or a separate signing process reached over JSON-RPC.
+#[derive(Debug, Clone)]
+pub enum BotSigner {
+    Local(LocalWallet),
+    Remote(RemoteSigner),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum BotSignerError {
+    #[error(transparent)]
+    Wallet(#[from] WalletError),
+    #[error("remote signer request failed: {0}")]
+    Transport(#[from] reqwest::Error),
+    #[error("remote signer error: {0}")]
+    Remote(String),
+    #[error("remote signer returned malformed data: {0}")]
+    Malformed(String),
+    #[error("{0} is not supported by the remote signer")]
+    Unsupported(&'static str),
+}
+
+impl BotSigner {
+    /// Builds every trading wallet described by `config`, in rotation order.
+    pub async fn load_all(config: &SignerConfig, chain_id: u64) -> Result<Vec<Self>> {
+        let signers = match config {
+            SignerConfig::PrivateKeyEnv => {
+                let key = std::env::var("TRADING_PRIVATE_KEY")
+                    .context("TRADING_PRIVATE_KEY env var missing")?;
+                vec![Self::Local(
+                    key.parse::<LocalWallet>().context("invalid private key")?,
+                )]
+            }
+            SignerConfig::Keystore {
+                paths,
+                password_file,
+            } => {
+                let shared_password = password_file
+                    .as_ref()
+                    .map(|file| {
+                        fs::read_to_string(file)
+                            .with_context(|| format!("read keystore password file {file:?}"))
+                            .map(|password| password.trim_end_matches(['\r', '\n']).to_string())
+                    })
+                    .transpose()?;
+
+                let mut signers = Vec::with_capacity(paths.len());
+                for path in paths {
+                    let password = match &shared_password {
+                        Some(password) => password.clone(),
+                        None => {
+                            rpassword::prompt_password(format!("Password for keystore {path:?}: "))
+                                .context("read keystore password")?
+                        }
+                    };
+                    signers.push(Self::Local(
+                        LocalWallet::decrypt_keystore(path, password)
+                            .with_context(|| format!("decrypt keystore {path:?}"))?,
+                    ));
+                }
+                signers
+            }
+            SignerConfig::Mnemonic { count } => {
+                let phrase = std::env::var("TRADING_MNEMONIC")
+                    .context("TRADING_MNEMONIC env var missing")?;
+                (0..*count)
+                    .map(|index| {
+                        MnemonicBuilder::<English>::default()
+                            .phrase(phrase.trim())
+                            .index(index)
+                            .and_then(|builder| builder.build())
+                            .map(Self::Local)
+                            .with_context(|| format!("derive wallet {index} from mnemonic"))
+                    })
+                    .collect::<Result<Vec<_>>>()?
+            }
+            SignerConfig::Remote { url, addresses } => {
+                if addresses.is_empty() {
+                    vec![Self::Remote(
+                        RemoteSigner::connect(url.clone(), None).await?,
+                    )]
+                } else {
+                    let mut signers = Vec::with_capacity(addresses.len());
+                    for address in addresses {
+                        signers.push(Self::Remote(
+                            RemoteSigner::connect(url.clone(), Some(*address)).await?,
+                        ));
+                    }
+                    signers
+                }
+            }
+        };
+
+        if signers.is_empty() {
+            return Err(anyhow!("signer configuration yields no wallets"));
+        }
+        Ok(signers
+            .into_iter()
+            .map(|signer| signer.with_chain_id(chain_id))
+            .collect())
+    }
+}
+
+#[async_trait]
+impl Signer for BotSigner {
+    type Error = BotSignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Self::Error> {
+        match self {
+            Self::Local(wallet) => Ok(wallet.sign_message(message).await?),
+            Self::Remote(remote) => remote.sign_message(message.as_ref()).await,
+        }
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        match self {
+            Self::Local(wallet) => Ok(wallet.sign_transaction(tx).await?),
+            Self::Remote(remote) => remote.sign_transaction(tx).await,
+        }
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        match self {
+            Self::Local(wallet) => Ok(wallet.sign_typed_data(payload).await?),
+            Self::Remote(_) => Err(BotSignerError::Unsupported("eip-712 signing")),
+        }
+    }
+
+    fn address(&self) -> Address {
+        match self {
+            Self::Local(wallet) => wallet.address(),
+            Self::Remote(remote) => remote.address,
+        }
+    }
+
+    fn chain_id(&self) -> u64 {
+        match self {
+            Self::Local(wallet) => wallet.chain_id(),
+            Self::Remote(remote) => remote.chain_id,
+        }
+    }
+
+    fn with_chain_id<T: Into<u64>>(self, chain_id: T) -> Self {
+        match self {
+            Self::Local(wallet) => Self::Local(wallet.with_chain_id(chain_id)),
+            Self::Remote(remote) => Self::Remote(RemoteSigner {
+                chain_id: chain_id.into(),
+                ..remote
+            }),
+        }
+    }
+}
+
+/// Signing process (Clef, web3signer, a hardware wallet bridge, ...) that
+/// exposes `eth_signTransaction` and `eth_sign` over JSON-RPC.
+#[derive(Debug, Clone)]
+pub struct RemoteSigner {
+    url: String,
+    address: Address,
+    chain_id: u64,
+    http: Client,
+}
+
+impl RemoteSigner {
+    /// Connects to the signer, asking it for its first account when no
+    /// address is configured.
+    async fn connect(url: String, address: Option<Address>) -> Result<Self> {
+        let mut signer = Self {
+            url,
+            address: address.unwrap_or_default(),
+            chain_id: 1,
+            http: Client::builder()
+                .timeout(Duration::from_secs(30))
+                .user_agent("agentic-memecoin-bot/1.0")
+                .build()
+                .expect("reqwest client build"),
+        };
+
+        if address.is_none() {
+            let accounts: Vec<Address> = serde_json::from_value(
+                signer
+                    .call("eth_accounts", json!([]))
+                    .await
+                    .context("list remote signer accounts")?,
+            )
+            .context("decode eth_accounts response")?;
+            signer.address = *accounts
+                .first()
+                .ok_or_else(|| anyhow!("remote signer exposes no accounts"))?;
+        }
+        Ok(signer)
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, BotSignerError> {
+        let mut request = tx.clone();
+        request.set_from(self.address);
+        request.set_chain_id(self.chain_id);
+
+        let result = self.call("eth_signTransaction", json!([request])).await?;
+        // Signers answer either with the raw transaction or `{ raw, tx }`.
+        let raw = result.get("raw").cloned().unwrap_or(result);
+        let raw: Bytes = serde_json::from_value(raw)
+            .map_err(|err| BotSignerError::Malformed(err.to_string()))?;
+        let (_, signature) = TypedTransaction::decode_signed(&Rlp::new(&raw))
+            .map_err(|err| BotSignerError::Malformed(err.to_string()))?;
+        Ok(signature)
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature, BotSignerError> {
+        let result = self
+            .call(
+                "eth_sign",
+                json!([self.address, Bytes::from(message.to_vec())]),
+            )
+            .await?;
+        let raw: Bytes = serde_json::from_value(result)
+            .map_err(|err| BotSignerError::Malformed(err.to_string()))?;
+        Signature::try_from(raw.as_ref()).map_err(|err| BotSignerError::Malformed(err.to_string()))
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value, BotSignerError> {
+        let resp: RpcResponse = self
+            .http
+            .post(&self.url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params,
+            }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        if let Some(error) = resp.error {
+            return Err(BotSignerError::Remote(format!(
+                "{method}: {} ({})",
+                error.message, error.code
+            )));
+        }
+        resp.result
+            .ok_or_else(|| BotSignerError::Malformed(format!("{method} returned no result")))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse {
+    #[serde(default)]
+    result: Option<Value>,
+    #[serde(default)]
+    error: Option<RpcError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
@@ -0,0 +1,330 @@
+use anyhow::{Result, bail};
+use ethers::types::Address;
+use time::OffsetDateTime;
+use tracing::debug;
+
+use crate::config::{BotConfig, StrategyConfig};
+
+use super::{
+    history::Candle, indicators::Indicators, risk::TokenRiskReport, scanner::GemCandidate,
+    trader::ExitReason,
+};
+
+/// Strategy names accepted by `STRATEGY`.
+pub const STRATEGIES: &[&str] = &["momentum"];
+
+/// Entry and exit rules. The engine and the backtester handle discovery,
+/// risk scoring, position limits, routing and execution, and ask the
+/// strategy whether and how to trade.
+pub trait Strategy: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Whether to buy a candidate that passed the discovery filters and
+    /// was scored by the risk policy, and with which size and targets.
+    fn should_enter(
+        &self,
+        candidate: &GemCandidate,
+        risk: &TokenRiskReport,
+        market: &MarketContext,
+    ) -> Option<EntryIntent>;
+
+    /// Whether to sell an open position at its current mark.
+    fn should_exit(&self, position: &PositionView, market: &MarketContext) -> Option<ExitIntent>;
+}
+
+/// Builds the strategy named in `config`.
+pub fn from_config(config: &StrategyConfig) -> Result<Box<dyn Strategy>> {
+    match config.name.as_str() {
+        "momentum" => Ok(Box::new(MomentumStrategy::new(config.clone()))),
+        other => bail!(
+            "unknown strategy {other}, expected one of: {}",
+            STRATEGIES.join(", ")
+        ),
+    }
+}
+
+/// One of the strategies run side by side, with its own position limit and
+/// budget.
+pub struct StrategyProfile {
+    pub config: StrategyConfig,
+    pub strategy: Box<dyn Strategy>,
+}
+
+impl StrategyProfile {
+    /// Every configured strategy, in configuration order. The first one
+    /// also owns positions without a known strategy.
+    pub fn load_all(config: &BotConfig) -> Result<Vec<Self>> {
+        config
+            .strategies()?
+            .into_iter()
+            .map(|config| {
+                Ok(Self {
+                    strategy: from_config(&config)?,
+                    config,
+                })
+            })
+            .collect()
+    }
+
+    pub fn id(&self) -> &str {
+        &self.config.id
+    }
+
+    pub fn is_full(&self, open_positions: usize) -> bool {
+        open_positions >= self.config.max_positions
+    }
+
+    /// Whether an entry costing `cost_usd` fits next to `exposure_usd` of
+    /// open positions.
+    pub fn fits_budget(&self, exposure_usd: f64, cost_usd: f64) -> bool {
+        self.config
+            .budget_usd
+            .is_none_or(|budget| exposure_usd + cost_usd <= budget)
+    }
+}
+
+/// What a strategy sees of a pair's market beyond its listing.
+#[derive(Debug, Clone, Copy)]
+pub struct MarketContext<'a> {
+    pub now: OffsetDateTime,
+    /// Candles of the pair, oldest first.
+    pub candles: &'a [Candle],
+}
+
+/// An open position as exit rules see it, for live and simulated positions
+/// alike.
+#[derive(Debug, Clone, Copy)]
+pub struct PositionView {
+    pub token: Address,
+    pub entry_at: OffsetDateTime,
+    pub entry_value_usd: f64,
+    /// What selling the whole position would return right now.
+    pub value_usd: f64,
+    pub take_profit_bps: u32,
+    pub stop_loss_bps: u32,
+}
+
+impl PositionView {
+    /// `None` for positions without an entry value.
+    pub fn pnl_bps(&self) -> Option<f64> {
+        (self.entry_value_usd > 0.0)
+            .then(|| (self.value_usd / self.entry_value_usd - 1.0) * 10_000.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EntryIntent {
+    pub size_eth: f64,
+    pub take_profit_bps: u32,
+    pub stop_loss_bps: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExitIntent {
+    pub reason: ExitReason,
+}
+
+/// The default strategy: enters risk-approved candidates with momentum that
+/// pass the indicator filters, and exits on the position's take-profit or
+/// stop-loss.
+pub struct MomentumStrategy {
+    config: StrategyConfig,
+}
+
+impl MomentumStrategy {
+    pub fn new(config: StrategyConfig) -> Self {
+        Self { config }
+    }
+
+    /// Weighted price change over the windows within the momentum horizon.
+    /// `None` when the momentum window is under 5 minutes or no weighted
+    /// window is left.
+    fn momentum_score(&self, candidate: &GemCandidate) -> Option<f64> {
+        if self.config.price_momentum_window_minutes < 5 {
+            return None;
+        }
+        let model = &self.config.momentum;
+        let weights = model
+            .price_weights
+            .iter()
+            .filter(|(window, _)| model.includes(**window))
+            .collect::<Vec<_>>();
+        if weights.is_empty() {
+            return None;
+        }
+        Some(
+            weights
+                .iter()
+                .map(|(window, weight)| candidate.price_change(**window) * **weight)
+                .sum(),
+        )
+    }
+
+    /// A candidate has momentum when its weighted price score, buy pressure
+    /// and volume acceleration all clear their thresholds.
+    fn has_momentum(&self, candidate: &GemCandidate) -> bool {
+        let model = &self.config.momentum;
+        let Some(score) = self.momentum_score(candidate) else {
+            return false;
+        };
+
+        let buying = model
+            .buy_pressure_windows
+            .iter()
+            .filter(|window| model.includes(**window))
+            .all(|window| candidate.buy_pressure(*window) >= model.min_buy_pressure);
+        let accelerating = model.min_volume_acceleration <= 0.0
+            || candidate.volume_acceleration() >= model.min_volume_acceleration;
+
+        debug!(
+            token = ?candidate.token_address,
+            score,
+            buying,
+            volume_acceleration = candidate.volume_acceleration(),
+            "momentum check"
+        );
+        score >= model.min_score && buying && accelerating
+    }
+}
+
+impl Strategy for MomentumStrategy {
+    fn name(&self) -> &'static str {
+        "momentum"
+    }
+
+    fn should_enter(
+        &self,
+        candidate: &GemCandidate,
+        risk: &TokenRiskReport,
+        market: &MarketContext,
+    ) -> Option<EntryIntent> {
+        if !risk.is_safe {
+            return None;
+        }
+        if !self.has_momentum(candidate) {
+            debug!(token = ?candidate.token_address, "insufficient momentum");
+            return None;
+        }
+        let indicators = Indicators::compute(market.candles, &self.config.indicators);
+        if let Some(reason) = indicators.entry_rejection(&self.config.indicators) {
+            debug!(token = ?candidate.token_address, reason, "indicator filters rejected entry");
+            return None;
+        }
+        Some(EntryIntent {
+            size_eth: self.config.position_size_eth,
+            take_profit_bps: self.config.take_profit_bps,
+            stop_loss_bps: indicators
+                .atr_stop_bps(&self.config.indicators)
+                .unwrap_or(self.config.stop_loss_bps),
+        })
+    }
+
+    fn should_exit(&self, position: &PositionView, market: &MarketContext) -> Option<ExitIntent> {
+        let pnl_bps = position.pnl_bps()?;
+        let reason = if pnl_bps >= position.take_profit_bps as f64 {
+            ExitReason::TakeProfit
+        } else if pnl_bps <= -(position.stop_loss_bps as f64) {
+            ExitReason::StopLoss
+        } else {
+            return None;
+        };
+        debug!(
+            token = ?position.token,
+            pnl_bps,
+            held_minutes = (market.now - position.entry_at).whole_minutes(),
+            reason = ?reason,
+            "exit target crossed"
+        );
+        Some(ExitIntent { reason })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// The baseline `.env.example` values: `MOMENTUM_WINDOW_MINUTES=15` and
+    /// the built-in momentum model.
+    fn baseline_strategy(overrides: serde_json::Value) -> MomentumStrategy {
+        let mut config = json!({
+            "max_positions": 4,
+            "position_size_eth": 0.3,
+            "price_momentum_window_minutes": 15
+        });
+        config
+            .as_object_mut()
+            .unwrap()
+            .extend(overrides.as_object().unwrap().clone());
+        MomentumStrategy::new(serde_json::from_value(config).unwrap())
+    }
+
+    fn candidate(m5: f64, m15: f64, h1: f64) -> GemCandidate {
+        GemCandidate {
+            pair_address: Address::zero(),
+            token_address: Address::zero(),
+            base_token: Address::zero(),
+            token_symbol: String::new(),
+            token_name: String::new(),
+            price_usd: 0.0,
+            liquidity_usd: 0.0,
+            volume24h_usd: 0.0,
+            volume_h6_usd: 0.0,
+            volume_h1_usd: 0.0,
+            fdv_usd: 0.0,
+            price_change_m5: m5,
+            price_change_m15: m15,
+            price_change_h1: h1,
+            price_change_h6: 0.0,
+            price_change_h24: 0.0,
+            buy_pressure_ratio: 0.6,
+            buy_pressure_m15: 0.0,
+            buy_pressure_h1: 0.0,
+            buy_pressure_h6: 0.0,
+            buy_pressure_h24: 0.0,
+            holder_count: None,
+            locked_liquidity_ratio: None,
+            contract_renounced_score: None,
+            pair_created_at: OffsetDateTime::now_utc(),
+            dex_id: String::new(),
+            pool_labels: Vec::new(),
+            confidence: 0.0,
+            safety_flags: Vec::new(),
+            usd_per_base: 0.0,
+            alternate_pools: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn baseline_settings_score_like_the_hard_coded_model() {
+        let strategy = baseline_strategy(json!({}));
+        let candidate = candidate(10.0, 6.0, 8.0);
+
+        let score = strategy.momentum_score(&candidate).unwrap();
+        let baseline = 10.0 * 0.4 + 6.0 * 0.35 + 8.0 * 0.25;
+        assert!((score - baseline).abs() < 1e-9);
+        // 8.1 only clears the 8.0 threshold with the h1 term.
+        assert!(strategy.has_momentum(&candidate));
+    }
+
+    #[test]
+    fn momentum_window_under_five_minutes_has_no_momentum() {
+        let strategy = baseline_strategy(json!({ "price_momentum_window_minutes": 4 }));
+        assert_eq!(strategy.momentum_score(&candidate(50.0, 50.0, 50.0)), None);
+    }
+
+    #[test]
+    fn horizon_leaves_out_longer_windows() {
+        let strategy = baseline_strategy(json!({ "momentum": { "horizon_minutes": 15 } }));
+        let candidate = candidate(10.0, 6.0, 8.0);
+
+        let score = strategy.momentum_score(&candidate).unwrap();
+        assert!((score - (10.0 * 0.4 + 6.0 * 0.35)).abs() < 1e-9);
+        assert!(!strategy.has_momentum(&candidate));
+        assert_eq!(
+            strategy.config.ignored_momentum_windows(),
+            [crate::config::MarketWindow::H1].into()
+        );
+    }
+}
@@ -1,32 +1,41 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    collections::HashSet,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
 
 use anyhow::{Context, Result, anyhow};
 use ethers::{
     contract::builders::ContractCall,
     middleware::SignerMiddleware,
     prelude::*,
-    providers::{Http, Provider},
-    utils::parse_units,
+    providers::Provider,
+    types::transaction::eip2718::TypedTransaction,
+    utils::{keccak256, parse_units},
 };
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
-use tokio::time::timeout;
-use tracing::{info, instrument};
+use tokio::{
+    sync::Mutex,
+    time::{sleep, timeout},
+};
+use tracing::{info, instrument, warn};
 
 use crate::config::BotConfig;
 
-use super::scanner::GemCandidate;
-
-abigen!(
-    UniswapV2Router,
-    r#"[
-        {"inputs":[{"internalType":"uint256","name":"amountIn","type":"uint256"},{"internalType":"address[]","name":"path","type":"address[]"}],"name":"getAmountsOut","outputs":[{"internalType":"uint256[]","name":"","type":"uint256[]"}],"stateMutability":"view","type":"function"},
-        {"inputs":[{"internalType":"uint256","name":"amountOutMin","type":"uint256"},{"internalType":"address[]","name":"path","type":"address[]"},{"internalType":"address","name":"to","type":"address"},{"internalType":"uint256","name":"deadline","type":"uint256"}],"name":"swapExactETHForTokensSupportingFeeOnTransferTokens","outputs":[],"stateMutability":"payable","type":"function"},
-        {"inputs":[{"internalType":"uint256","name":"amountIn","type":"uint256"},{"internalType":"uint256","name":"amountOutMin","type":"uint256"},{"internalType":"address[]","name":"path","type":"address[]"},{"internalType":"address","name":"to","type":"address"},{"internalType":"uint256","name":"deadline","type":"uint256"}],"name":"swapExactTokensForETHSupportingFeeOnTransferTokens","outputs":[],"stateMutability":"nonpayable","type":"function"}
-    ]"#,
-    event_derives(serde::Deserialize, serde::Serialize)
-);
+use super::{
+    approvals::{ApprovalLedger, ApprovalRecord},
+    http::HttpClient,
+    oracle::PriceOracle,
+    relay::PrivateRelay,
+    rpc::RpcPool,
+    scanner::GemCandidate,
+    signer::BotSigner,
+    venue::{LiquidityPool, PoolKind, SwapLeg, SwapRoute, VenueRegistry},
+};
 
 abigen!(
     Erc20,
@@ -42,15 +51,32 @@ abigen!(
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionResult {
     pub tx_hash: TxHash,
+    #[serde(default)]
+    pub wallet: Address,
+    pub route: SwapRoute,
     pub token_address: Address,
     pub base_token: Address,
     pub base_spent: U256,
     pub tokens_acquired: U256,
+    #[serde(default)]
+    pub price_impact_bps: f64,
     pub block_number: U64,
     pub timestamp: OffsetDateTime,
 }
 
-type SigningMiddleware = SignerMiddleware<Arc<Provider<Http>>, Wallet<k256::ecdsa::SigningKey>>;
+/// Trade size that passed the price impact guard, with the impact expected at
+/// that size.
+#[derive(Debug, Clone, Copy)]
+pub struct ImpactCheck {
+    pub amount_in: U256,
+    pub impact_bps: f64,
+}
+
+const MAX_DOWNSIZE_STEPS: usize = 4;
+/// Gas set aside for an entry swap when picking a wallet to fund it.
+const ENTRY_GAS_RESERVE: u64 = 400_000;
+
+pub(super) type SigningMiddleware = SignerMiddleware<Arc<Provider<RpcPool>>, BotSigner>;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ExitReason {
@@ -60,99 +86,272 @@ pub enum ExitReason {
     Manual,
 }
 
+impl ExitReason {
+    /// Exits that protect capital, which go through even when no size
+    /// fits the price impact limit.
+    pub fn is_protective(&self) -> bool {
+        matches!(self, Self::StopLoss | Self::RiskAlert)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExitOrder {
     pub position_id: uuid::Uuid,
+    pub wallet: Address,
     pub token_address: Address,
     pub base_token: Address,
     pub token_amount: U256,
     pub min_output: U256,
+    pub route: SwapRoute,
+    pub price_impact_bps: f64,
     pub reason: ExitReason,
 }
 
+/// Outcome of revoking one recorded allowance.
+#[derive(Debug, Clone)]
+pub struct RevokedApproval {
+    pub owner: Address,
+    pub token: Address,
+    pub spender: Address,
+    pub remaining: U256,
+    pub tx_hash: Option<TxHash>,
+    /// Why the allowance was left in place, e.g. its owner wallet is no
+    /// longer loaded.
+    pub skipped: Option<String>,
+}
+
+struct TradingWallet {
+    address: Address,
+    client: Arc<SigningMiddleware>,
+}
+
 pub struct Trader {
     config: BotConfig,
-    provider: Arc<Provider<Http>>,
-    client: Arc<SigningMiddleware>,
-    router: UniswapV2Router<SigningMiddleware>,
-    wallet_address: Address,
-    http: Client,
+    provider: Arc<Provider<RpcPool>>,
+    wallets: Vec<TradingWallet>,
+    next_wallet: AtomicUsize,
+    venues: VenueRegistry,
+    relay: Option<PrivateRelay>,
+    approvals: Mutex<ApprovalLedger>,
+    oracle: PriceOracle,
 }
 
 impl Trader {
-    pub async fn new(config: BotConfig) -> Result<Self> {
-        let http_provider = Provider::<Http>::try_from(config.rpc.http_url.clone())
-            .context("initializing HTTP provider")?
-            .interval(Duration::from_millis(config.rpc.poll_interval_ms));
-
-        let provider = Arc::new(http_provider);
-        let key =
-            std::env::var("TRADING_PRIVATE_KEY").context("TRADING_PRIVATE_KEY env var missing")?;
-        let wallet: LocalWallet = key
-            .parse::<LocalWallet>()
-            .context("invalid private key")?
-            .with_chain_id(config.chain as u64);
-
-        let client = Arc::new(SignerMiddleware::new(provider.clone(), wallet));
-
-        let router = UniswapV2Router::new(config.exchange.router_address, client.clone());
-        let wallet_address = client.address();
-        let http = Client::builder()
-            .timeout(Duration::from_secs(10))
-            .user_agent("agentic-memecoin-bot/1.0")
-            .build()
-            .expect("reqwest client build");
+    pub async fn new(config: BotConfig, http: HttpClient) -> Result<Self> {
+        let rpc = RpcPool::new(&config.rpc).context("initializing RPC endpoints")?;
+        let provider = Arc::new(
+            Provider::new(rpc).interval(Duration::from_millis(config.rpc.poll_interval_ms)),
+        );
+        let wallets = BotSigner::load_all(&config.signer, config.chain as u64)
+            .await
+            .context("initializing signers")?
+            .into_iter()
+            .map(|signer| {
+                let client = Arc::new(SignerMiddleware::new(provider.clone(), signer));
+                TradingWallet {
+                    address: client.address(),
+                    client,
+                }
+            })
+            .collect::<Vec<_>>();
+        let primary = wallets
+            .first()
+            .ok_or_else(|| anyhow!("no trading wallets configured"))?;
 
+        // Venue contracts only build calldata and quotes; transactions are sent
+        // through the owning wallet's client.
+        let venues = VenueRegistry::new(
+            &config.exchange.venues,
+            primary.client.clone(),
+            provider.clone(),
+        )
+        .context("building venue registry")?;
         Ok(Self {
-            config,
+            wallets,
+            next_wallet: AtomicUsize::new(0),
+            venues,
+            relay: config.exchange.private_relay.clone().map(PrivateRelay::new),
+            approvals: Mutex::new(ApprovalLedger::load().context("load approval ledger")?),
+            oracle: PriceOracle::new(&config, provider.clone(), http),
             provider,
-            client,
-            router,
-            wallet_address,
-            http,
+            config,
         })
     }
 
-    pub fn provider(&self) -> Arc<Provider<Http>> {
+    pub fn provider(&self) -> Arc<Provider<RpcPool>> {
         self.provider.clone()
     }
 
+    pub fn wallet_addresses(&self) -> Vec<Address> {
+        self.wallets.iter().map(|wallet| wallet.address).collect()
+    }
+
+    /// Wallet that owns `address`. Positions and approvals recorded before
+    /// wallet rotation carry a zero owner and map to the primary wallet.
+    fn wallet(&self, address: Address) -> Result<&TradingWallet> {
+        if address.is_zero() {
+            return Ok(&self.wallets[0]);
+        }
+        self.wallets
+            .iter()
+            .find(|wallet| wallet.address == address)
+            .ok_or_else(|| anyhow!("wallet {address:?} is not loaded"))
+    }
+
+    /// Next wallet in the rotation holding at least `required` of the
+    /// native coin, the swap amount plus its gas.
+    async fn next_entry_wallet(&self, required: U256) -> Result<&TradingWallet> {
+        let start = self.next_wallet.fetch_add(1, Ordering::Relaxed);
+        for offset in 0..self.wallets.len() {
+            let wallet = &self.wallets[(start + offset) % self.wallets.len()];
+            let balance = self.native_balance(wallet.address).await?;
+            if balance >= required {
+                return Ok(wallet);
+            }
+        }
+        Err(anyhow!(
+            "no trading wallet holds the {required} wei an entry needs with gas"
+        ))
+    }
+
+    pub async fn native_balance(&self, wallet: Address) -> Result<U256> {
+        self.provider
+            .get_balance(self.wallet(wallet)?.address, None)
+            .await
+            .context("fetching wallet native balance")
+    }
+
+    /// Sends a swap through the private relay when one is configured, keeping
+    /// it out of the public mempool. If the relay errors or the transaction is
+    /// not included within the relay's block window, the same signed
+    /// transaction is broadcast publicly unless fallback is disabled.
+    async fn broadcast<'a>(
+        &'a self,
+        wallet: &'a TradingWallet,
+        mut tx: TypedTransaction,
+    ) -> Result<PendingTransaction<'a, RpcPool>> {
+        let Some(relay) = &self.relay else {
+            return wallet
+                .client
+                .send_transaction(tx, None)
+                .await
+                .context("submit tx to public mempool");
+        };
+
+        wallet
+            .client
+            .fill_transaction(&mut tx, None)
+            .await
+            .context("fill transaction")?;
+        let signature = wallet
+            .client
+            .signer()
+            .sign_transaction(&tx)
+            .await
+            .context("sign transaction")?;
+        let raw_tx = tx.rlp_signed(&signature);
+        let tx_hash = H256::from(keccak256(&raw_tx));
+        let submitted_at = self
+            .provider
+            .get_block_number()
+            .await
+            .context("fetch block number for relay")?;
+
+        match relay
+            .submit(&raw_tx, submitted_at, wallet.client.signer())
+            .await
+        {
+            Ok(()) => {
+                info!(tx = ?tx_hash, "submitted transaction to private relay");
+                let last_block = submitted_at + relay.max_blocks();
+                loop {
+                    if self
+                        .provider
+                        .get_transaction_receipt(tx_hash)
+                        .await
+                        .context("poll private tx receipt")?
+                        .is_some()
+                    {
+                        return Ok(PendingTransaction::new(tx_hash, self.provider.as_ref()));
+                    }
+                    if self.provider.get_block_number().await? > last_block {
+                        break;
+                    }
+                    sleep(Duration::from_millis(self.config.rpc.poll_interval_ms)).await;
+                }
+                warn!(tx = ?tx_hash, "private transaction not included within relay window");
+            }
+            Err(err) => warn!(tx = ?tx_hash, error = ?err, "private relay submission failed"),
+        }
+
+        if !relay.fallback_to_public() {
+            return Err(anyhow!(
+                "private transaction {tx_hash:?} not included and public fallback disabled"
+            ));
+        }
+
+        match self.provider.send_raw_transaction(raw_tx).await {
+            Ok(pending) => Ok(pending),
+            // The relay may still have landed it while we were switching over.
+            Err(err) => match self.provider.get_transaction_receipt(tx_hash).await? {
+                Some(_) => Ok(PendingTransaction::new(tx_hash, self.provider.as_ref())),
+                None => Err(err).context("public fallback broadcast"),
+            },
+        }
+    }
+
+    /// Picks the venue and pool the candidate's pair actually lives on,
+    /// refusing pairs on venues that are not registered for this chain.
+    pub async fn resolve_route(
+        &self,
+        candidate: &GemCandidate,
+        amount_in: U256,
+    ) -> Result<SwapRoute> {
+        self.venues.resolve(candidate, amount_in).await
+    }
+
     #[instrument(skip(self, candidate))]
     pub async fn execute_entry(
         &self,
         token: &Address,
-        amount_in: U256,
+        sized: ImpactCheck,
         candidate: &GemCandidate,
+        route: SwapRoute,
     ) -> Result<ExecutionResult> {
-        let path = vec![candidate.base_token, *token];
+        let amount_in = sized.amount_in;
         let expected_tokens = self
-            .quote_buy(token, amount_in, candidate.base_token)
+            .quote_buy(token, amount_in, candidate.base_token, route)
             .await?;
         let min_out = expected_tokens
             .checked_mul(U256::from(10_000u64 - self.config.slippage_bps() as u64))
             .ok_or_else(|| anyhow!("slippage multiplication overflow"))?
             / U256::from(10_000u64);
 
-        let recipient = self.wallet_address;
+        let gas_price: U256 = parse_units(self.config.exchange.max_gas_price_gwei, "gwei")?.into();
+        let wallet = self
+            .next_entry_wallet(amount_in + gas_price * U256::from(ENTRY_GAS_RESERVE))
+            .await?;
+        let recipient = wallet.address;
         let deadline = U256::from(
             (OffsetDateTime::now_utc() + self.config.swap_deadline()).unix_timestamp() as u64,
         );
 
-        let balance_before = self.token_balance(token).await?;
+        let balance_before = self.token_balance(wallet.address, token).await?;
 
-        let mut call: ContractCall<_, ()> = self
-            .router
-            .method(
-                "swapExactETHForTokensSupportingFeeOnTransferTokens",
-                (min_out, path.clone(), recipient, deadline),
-            )
-            .context("prepare swapExactETHForTokens call")?;
-        call = call.value(amount_in).gas_price(parse_units(
-            self.config.exchange.max_gas_price_gwei,
-            "gwei",
-        )?);
+        let mut tx = self.venues.get(&route)?.buy_with_native_tx(
+            &SwapLeg {
+                token_in: candidate.base_token,
+                token_out: *token,
+                amount_in,
+                min_out,
+                recipient,
+                deadline,
+            },
+            route.pool,
+        )?;
+        tx.set_gas_price(gas_price);
 
-        let pending_tx = call.send().await.context("submit swap tx")?;
+        let pending_tx = self.broadcast(wallet, tx).await.context("submit swap tx")?;
 
         let receipt = timeout(
             self.config.swap_deadline() + Duration::from_secs(30),
@@ -167,7 +366,7 @@ impl Trader {
             .block_number
             .context("missing block number in receipt")?;
 
-        let balance_after = self.token_balance(token).await?;
+        let balance_after = self.token_balance(wallet.address, token).await?;
         let tokens_acquired = balance_after
             .checked_sub(balance_before)
             .ok_or_else(|| anyhow!("token balance decreased unexpectedly"))?;
@@ -182,10 +381,13 @@ impl Trader {
 
         let execution = ExecutionResult {
             tx_hash: receipt.transaction_hash,
+            wallet: wallet.address,
+            route,
             token_address: *token,
             base_token: candidate.base_token,
             base_spent: amount_in,
             tokens_acquired,
+            price_impact_bps: sized.impact_bps,
             block_number,
             timestamp,
         };
@@ -193,6 +395,10 @@ impl Trader {
         info!(
             token = ?token,
             tx = ?execution.tx_hash,
+            wallet = ?wallet.address,
+            impact_bps = execution.price_impact_bps,
+            venue = ?route.router,
+            pool = ?route.pool,
             tokens = %execution.tokens_acquired,
             "entry execution completed"
         );
@@ -201,51 +407,57 @@ impl Trader {
     }
 
     pub async fn execute_exit(&self, exit_order: &ExitOrder) -> Result<ExecutionResult> {
-        let path = vec![exit_order.token_address, exit_order.base_token];
+        let wallet = self.wallet(exit_order.wallet)?;
         let deadline = U256::from(
             (OffsetDateTime::now_utc() + self.config.swap_deadline()).unix_timestamp() as u64,
         );
-        let mut tx: ContractCall<_, ()> = self
-            .router
-            .method(
-                "swapExactTokensForETHSupportingFeeOnTransferTokens",
-                (
-                    exit_order.token_amount,
-                    exit_order.min_output,
-                    path.clone(),
-                    self.wallet_address,
-                    deadline,
-                ),
-            )
-            .context("prepare swapExactTokensForETH call")?;
-        tx = tx.gas_price(parse_units(
-            self.config.exchange.max_gas_price_gwei,
-            "gwei",
-        )?);
+        let gas_price = parse_units(self.config.exchange.max_gas_price_gwei, "gwei")?;
 
-        self.ensure_allowance(exit_order.token_address, exit_order.token_amount)
-            .await?;
+        let mut tx = self.venues.get(&exit_order.route)?.sell_for_native_tx(
+            &SwapLeg {
+                token_in: exit_order.token_address,
+                token_out: exit_order.base_token,
+                amount_in: exit_order.token_amount,
+                min_out: exit_order.min_output,
+                recipient: wallet.address,
+                deadline,
+            },
+            exit_order.route.pool,
+        )?;
+        tx.set_gas_price(gas_price);
+
+        self.ensure_allowance(
+            wallet,
+            exit_order.token_address,
+            exit_order.token_amount,
+            exit_order.route,
+        )
+        .await?;
 
         let base_balance_before = self
             .provider
-            .get_balance(self.wallet_address, None)
+            .get_balance(wallet.address, None)
             .await
             .context("fetch native balance before exit")?;
 
-        let pending = tx
-            .send()
+        let pending = self
+            .broadcast(wallet, tx)
             .await
             .context("sending exit transaction to router")?;
 
-        let receipt = pending
-            .await
-            .context("exit tx dropped")?
-            .ok_or_else(|| anyhow!("exit transaction dropped without receipt"))?;
+        let receipt = timeout(
+            self.config.swap_deadline() + Duration::from_secs(30),
+            pending,
+        )
+        .await
+        .context("exit tx timeout")?
+        .context("exit tx dropped")?
+        .ok_or_else(|| anyhow!("exit transaction dropped without receipt"))?;
         let block_number = receipt.block_number.context("missing exit block number")?;
 
         let base_balance_after = self
             .provider
-            .get_balance(self.wallet_address, None)
+            .get_balance(wallet.address, None)
             .await
             .context("fetch native balance after exit")?;
 
@@ -261,14 +473,157 @@ impl Trader {
             .and_then(|ts| OffsetDateTime::from_unix_timestamp(ts.as_u64() as i64).ok())
             .unwrap_or_else(OffsetDateTime::now_utc);
 
-        Ok(ExecutionResult {
+        let execution = ExecutionResult {
             tx_hash: receipt.transaction_hash,
+            wallet: wallet.address,
+            route: exit_order.route,
             token_address: exit_order.token_address,
             base_token: exit_order.base_token,
             base_spent: redeemed,
             tokens_acquired: exit_order.token_amount,
+            price_impact_bps: exit_order.price_impact_bps,
             block_number,
             timestamp,
+        };
+
+        info!(
+            token = ?exit_order.token_address,
+            tx = ?execution.tx_hash,
+            wallet = ?wallet.address,
+            impact_bps = execution.price_impact_bps,
+            reason = ?exit_order.reason,
+            redeemed = %execution.base_spent,
+            "exit execution completed"
+        );
+
+        Ok(execution)
+    }
+
+    /// Applies the configured price impact limit to a swap of `amount_in`
+    /// through `pair`. Oversized trades are shrunk towards the limit when
+    /// downsizing is enabled; `None` means the trade should be skipped.
+    pub async fn fit_price_impact(
+        &self,
+        pair: Address,
+        token_in: Address,
+        token_out: Address,
+        amount_in: U256,
+        route: SwapRoute,
+    ) -> Result<Option<ImpactCheck>> {
+        let limit = self.config.exchange.max_price_impact_bps as f64;
+        let mut amount = amount_in;
+        let mut impact = self
+            .price_impact_bps(pair, token_in, token_out, amount, route)
+            .await?;
+
+        if impact <= limit {
+            return Ok(Some(ImpactCheck {
+                amount_in: amount,
+                impact_bps: impact,
+            }));
+        }
+        if !self.config.exchange.downsize_on_price_impact {
+            return Ok(None);
+        }
+
+        for _ in 0..MAX_DOWNSIZE_STEPS {
+            // Impact grows slightly faster than linearly with size, so aim a bit
+            // under the linear estimate and re-measure.
+            let scale_bps = ((limit / impact) * 9_500.0) as u64;
+            amount = amount * U256::from(scale_bps) / U256::from(10_000u64);
+            if amount.is_zero() {
+                return Ok(None);
+            }
+            impact = self
+                .price_impact_bps(pair, token_in, token_out, amount, route)
+                .await?;
+            if impact <= limit {
+                info!(
+                    pair = ?pair,
+                    requested = %amount_in,
+                    downsized = %amount,
+                    impact_bps = impact,
+                    "downsized trade to respect price impact limit"
+                );
+                return Ok(Some(ImpactCheck {
+                    amount_in: amount,
+                    impact_bps: impact,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Expected price impact of swapping `amount_in` through the route's pool,
+    /// in basis points. Constant-product pools are measured from `getReserves`;
+    /// concentrated-liquidity and Solidly stable pools, whose curves are not
+    /// constant-product, compare the quote against a small probe.
+    pub async fn price_impact_bps(
+        &self,
+        pair: Address,
+        token_in: Address,
+        token_out: Address,
+        amount_in: U256,
+        route: SwapRoute,
+    ) -> Result<f64> {
+        if matches!(
+            route.pool,
+            PoolKind::V3 { .. } | PoolKind::Solidly { stable: true }
+        ) || pair.is_zero()
+        {
+            let probe = amount_in / U256::from(1_000u64);
+            if probe.is_zero() {
+                return Ok(0.0);
+            }
+            let probe_out = self.quote(token_in, token_out, probe, route).await?;
+            let full_out = self.quote(token_in, token_out, amount_in, route).await?;
+            let expected = to_f64(probe_out) * 1_000.0;
+            if expected <= 0.0 {
+                return Err(anyhow!("probe quote returned nothing for pair {pair:?}"));
+            }
+            return Ok(((1.0 - to_f64(full_out) / expected) * 10_000.0).max(0.0));
+        }
+
+        let pool = LiquidityPool::new(pair, self.provider.clone());
+        let (reserve0, reserve1, _) = pool
+            .get_reserves()
+            .call()
+            .await
+            .context("read pair reserves")?;
+        let token0 = pool.token_0().call().await.context("read pair token0")?;
+        let reserve_in = if token0 == token_in {
+            reserve0
+        } else {
+            reserve1
+        };
+        if reserve_in.is_zero() {
+            return Err(anyhow!("pair {pair:?} has no reserves"));
+        }
+
+        // Constant product: the fill price is worse than spot by dx / (x + dx).
+        let impact = to_f64(amount_in) / to_f64(reserve_in + amount_in);
+        Ok(impact * 10_000.0)
+    }
+
+    /// Spot price of `token` in raw `base_token` units per raw token unit and
+    /// the pool's base token balance. Concentrated-liquidity pools are read
+    /// from `slot0`, the others from their reserves.
+    pub async fn pool_spot(
+        &self,
+        pair: Address,
+        token: Address,
+        base_token: Address,
+        concentrated: bool,
+    ) -> Result<PoolSpot> {
+        let base_per_token = pool_price(&self.provider, pair, token, concentrated).await?;
+        let base_reserve = Erc20::new(base_token, self.provider.clone())
+            .balance_of(pair)
+            .call()
+            .await
+            .context("read pool base balance")?;
+        Ok(PoolSpot {
+            base_per_token,
+            base_reserve,
         })
     }
 
@@ -277,13 +632,9 @@ impl Trader {
         token: &Address,
         amount_in: U256,
         base_token: Address,
+        route: SwapRoute,
     ) -> Result<U256> {
-        let path = vec![base_token, *token];
-        let result = self.router.get_amounts_out(amount_in, path).call().await?;
-        result
-            .last()
-            .cloned()
-            .ok_or_else(|| anyhow!("router getAmountsOut returned empty path"))
+        self.quote(base_token, *token, amount_in, route).await
     }
 
     pub async fn quote_sell(
@@ -291,27 +642,36 @@ impl Trader {
         token: &Address,
         amount_in: U256,
         base_token: Address,
+        route: SwapRoute,
     ) -> Result<U256> {
-        let path = vec![*token, base_token];
-        let result = self.router.get_amounts_out(amount_in, path).call().await?;
-        result
-            .last()
-            .cloned()
-            .ok_or_else(|| anyhow!("router getAmountsOut returned empty path"))
+        self.quote(*token, base_token, amount_in, route).await
     }
 
-    pub async fn token_balance(&self, token: &Address) -> Result<U256> {
-        if *token == self.wallet_address {
+    async fn quote(
+        &self,
+        token_in: Address,
+        token_out: Address,
+        amount_in: U256,
+        route: SwapRoute,
+    ) -> Result<U256> {
+        self.venues
+            .get(&route)?
+            .quote(token_in, token_out, amount_in, route.pool)
+            .await
+    }
+
+    pub async fn token_balance(&self, wallet: Address, token: &Address) -> Result<U256> {
+        if *token == wallet {
             return self
                 .provider
-                .get_balance(self.wallet_address, None)
+                .get_balance(wallet, None)
                 .await
                 .context("fetching wallet eth balance");
         }
 
         let erc20 = Erc20::new(*token, self.provider.clone());
         erc20
-            .balance_of(self.wallet_address)
+            .balance_of(wallet)
             .call()
             .await
             .context("fetching erc20 balance")
@@ -326,69 +686,501 @@ impl Trader {
             .context("fetch token decimals")
     }
 
-    async fn ensure_allowance(&self, token: Address, amount: U256) -> Result<()> {
-        if token == self.wallet_address {
+    async fn ensure_allowance(
+        &self,
+        wallet: &TradingWallet,
+        token: Address,
+        amount: U256,
+        route: SwapRoute,
+    ) -> Result<()> {
+        if token == wallet.address {
             return Ok(());
         }
-        let erc20 = Erc20::new(token, self.client.clone());
-        let allowance = erc20
-            .allowance(self.wallet_address, self.config.exchange.router_address)
-            .call()
-            .await?;
+        let spender = self.venues.get(&route)?.router_address;
+        let erc20 = Erc20::new(token, wallet.client.clone());
+        let allowance = erc20.allowance(wallet.address, spender).call().await?;
         if allowance >= amount {
             return Ok(());
         }
 
-        let mut approval: ContractCall<_, bool> =
-            erc20.approve(self.config.exchange.router_address, U256::max_value());
+        let approved = self.config.exchange.approval_policy.allowance_for(amount);
+        let tx_hash = self.approve(wallet, token, spender, approved).await?;
+        info!(
+            wallet = ?wallet.address,
+            token = ?token,
+            spender = ?spender,
+            amount = %approved,
+            tx = ?tx_hash,
+            "granted router allowance"
+        );
+
+        let mut approvals = self.approvals.lock().await;
+        approvals.record(ApprovalRecord {
+            owner: wallet.address,
+            token,
+            spender,
+            amount: approved,
+            tx_hash,
+            granted_at: OffsetDateTime::now_utc(),
+        });
+        approvals.persist()
+    }
+
+    async fn approve(
+        &self,
+        wallet: &TradingWallet,
+        token: Address,
+        spender: Address,
+        amount: U256,
+    ) -> Result<TxHash> {
+        let erc20 = Erc20::new(token, wallet.client.clone());
+        let mut approval: ContractCall<_, bool> = erc20.approve(spender, amount);
         approval = approval.gas_price(parse_units(
             self.config.exchange.max_gas_price_gwei,
             "gwei",
         )?);
-        let pending = approval.send().await?;
-        pending.await?;
-        Ok(())
+        let pending = approval.send().await.context("send approve tx")?;
+        let receipt = pending
+            .await
+            .context("approve tx dropped")?
+            .ok_or_else(|| anyhow!("approve transaction dropped without receipt"))?;
+        Ok(receipt.transaction_hash)
+    }
+
+    /// Zeroes every recorded allowance whose `(wallet, token)` pair is not in
+    /// `held`. Allowances already spent down to zero are simply dropped from
+    /// the ledger.
+    pub async fn revoke_approvals(
+        &self,
+        held: &HashSet<(Address, Address)>,
+        dry_run: bool,
+    ) -> Result<Vec<RevokedApproval>> {
+        let records = self.approvals.lock().await.records();
+        let mut revoked = Vec::new();
+
+        for record in records {
+            if held.contains(&(record.owner, record.token)) {
+                continue;
+            }
+
+            let remaining = Erc20::new(record.token, self.provider.clone())
+                .allowance(record.owner, record.spender)
+                .call()
+                .await
+                .context("fetch recorded allowance")?;
+
+            // The record stays, so the allowance is revoked once the wallet
+            // is loaded again.
+            let wallet = match self.wallet(record.owner) {
+                Ok(wallet) => wallet,
+                Err(err) => {
+                    warn!(owner = ?record.owner, token = ?record.token, error = %err, "skipping approval of an unloaded wallet");
+                    revoked.push(RevokedApproval {
+                        owner: record.owner,
+                        token: record.token,
+                        spender: record.spender,
+                        remaining,
+                        tx_hash: None,
+                        skipped: Some("wallet not loaded".to_string()),
+                    });
+                    continue;
+                }
+            };
+
+            let tx_hash = if remaining.is_zero() || dry_run {
+                None
+            } else {
+                Some(
+                    self.approve(wallet, record.token, record.spender, U256::zero())
+                        .await?,
+                )
+            };
+
+            if !dry_run {
+                let mut approvals = self.approvals.lock().await;
+                approvals.remove(record.owner, record.token, record.spender);
+                approvals.persist()?;
+            }
+
+            revoked.push(RevokedApproval {
+                owner: wallet.address,
+                token: record.token,
+                spender: record.spender,
+                remaining,
+                tx_hash,
+                skipped: None,
+            });
+        }
+
+        Ok(revoked)
     }
 
     pub async fn fetch_token_price_usd(
         &self,
         token: &Address,
         base_token: Address,
+        route: SwapRoute,
         usd_price_per_base: f64,
     ) -> Result<f64> {
-        let amount_out = self.quote_sell(token, U256::exp10(18), base_token).await?;
+        let amount_out = self
+            .quote_sell(token, U256::exp10(18), base_token, route)
+            .await?;
         let base_decimals = self.token_decimals(base_token).await.unwrap_or(18);
         let base_float = ethers::utils::format_units(amount_out, u32::from(base_decimals))?;
         Ok(base_float.parse::<f64>()? * usd_price_per_base)
     }
 
+    /// USD price of `base_token` from its on-chain feed, cross-checked
+    /// against DefiLlama, or from DefiLlama alone when it has no feed.
     pub async fn fetch_base_usd_price(&self, base_token: Address) -> Result<f64> {
-        let chain = self.config.chain_as_str();
-        let key = format!("{chain}:{base_token:?}");
-        let url = format!("https://coins.llama.fi/prices/current/{key}");
-
-        let resp: LlamaPriceResponse = self
-            .http
-            .get(url)
-            .send()
-            .await?
-            .error_for_status()?
-            .json()
-            .await?;
+        self.oracle.base_usd_price(base_token).await
+    }
+}
+
+/// On-chain pool state used for price sampling.
+#[derive(Debug, Clone, Copy)]
+pub struct PoolSpot {
+    /// Raw base token units per raw token unit.
+    pub base_per_token: f64,
+    pub base_reserve: U256,
+}
+
+/// Raw units of the other token per raw unit of `token` in `pair`, from
+/// `slot0` for concentrated-liquidity pools and the reserves otherwise.
+pub(super) async fn pool_price(
+    provider: &Arc<Provider<RpcPool>>,
+    pair: Address,
+    token: Address,
+    concentrated: bool,
+) -> Result<f64> {
+    let pool = LiquidityPool::new(pair, provider.clone());
+    let token0 = pool.token_0().call().await.context("read pair token0")?;
 
-        resp.coins
-            .get(&key)
-            .map(|price| price.price)
-            .ok_or_else(|| anyhow!("missing price data for base token"))
+    let price = if concentrated {
+        let (sqrt_price_x96, ..) = pool.slot_0().call().await.context("read pool slot0")?;
+        let sqrt_price = to_f64(sqrt_price_x96) / 2f64.powi(96);
+        // slot0 prices token0 in token1.
+        let token1_per_token0 = sqrt_price * sqrt_price;
+        if token0 == token {
+            token1_per_token0
+        } else {
+            1.0 / token1_per_token0
+        }
+    } else {
+        let (reserve0, reserve1, _) = pool
+            .get_reserves()
+            .call()
+            .await
+            .context("read pair reserves")?;
+        let (token_reserve, other_reserve) = if token0 == token {
+            (reserve0, reserve1)
+        } else {
+            (reserve1, reserve0)
+        };
+        to_f64(other_reserve) / to_f64(token_reserve)
+    };
+    if !price.is_finite() || price <= 0.0 {
+        return Err(anyhow!("pair {pair:?} has no usable price"));
     }
+    Ok(price)
 }
 
-#[derive(Debug, Deserialize)]
-struct LlamaPriceResponse {
-    coins: std::collections::HashMap<String, LlamaPriceEntry>,
+fn to_f64(amount: U256) -> f64 {
+    ethers::utils::format_units(amount, 0u32)
+        .unwrap_or_else(|_| "0".to_string())
+        .parse::<f64>()
+        .unwrap_or(0.0)
 }
 
-#[derive(Debug, Deserialize)]
-struct LlamaPriceEntry {
-    price: f64,
+#[cfg(test)]
+mod tests {
+    use axum::{Json, Router, extract::State, routing::post};
+    use ethers::utils::{Anvil, parse_ether};
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::engine::relay::tests::{KEY, Received, stand_in_relay};
+
+    const WETH: &str = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
+    const UNI: &str = "0x1f9840a85d5aF5bf1D1762F925BDADdC4201F984";
+    const UNI_WETH_V2: &str = "0xd3d2E2692501A5c9Ca623199D38826e513033a17";
+    const UNI_WETH_V3: &str = "0x1d42064Fc4Beb5F8aAF85F4617AE8b3b5B8Bd801";
+
+    /// Mainnet's Uniswap V2 and V3 venues, signing with anvil's unlocked
+    /// accounts.
+    fn fork_config(rpc_url: &str) -> BotConfig {
+        serde_json::from_value(json!({
+            "chain": "mainnet",
+            "rpc": { "http_urls": [rpc_url] },
+            "strategy": { "max_positions": 1, "position_size_eth": 0.1 },
+            "exchange": {
+                "venues": [
+                    {
+                        "dex_id": "uniswap",
+                        "protocol": "uniswap_v2",
+                        "router_address": "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D",
+                        "factory_address": "0x5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f"
+                    },
+                    {
+                        "dex_id": "uniswap",
+                        "protocol": "uniswap_v3",
+                        "router_address": "0xE592427A0AEce9168e818045924c65bE2f64f4Ba",
+                        "factory_address": "0x1F98431c8aD98523631AE4a59f272d9d2dA37Fc1",
+                        "quoter_address": "0xb27308f9F90D607463bb33eA1BeBb41C27CE5AB6"
+                    }
+                ]
+            },
+            "risk": {},
+            "signer": { "kind": "remote", "url": rpc_url }
+        }))
+        .expect("fork config")
+    }
+
+    fn uni_candidate(pair: &str, labels: &[&str]) -> GemCandidate {
+        GemCandidate {
+            pair_address: pair.parse().unwrap(),
+            token_address: UNI.parse().unwrap(),
+            base_token: WETH.parse().unwrap(),
+            token_symbol: "UNI".to_string(),
+            token_name: "Uniswap".to_string(),
+            price_usd: 0.0,
+            liquidity_usd: 0.0,
+            volume24h_usd: 0.0,
+            volume_h6_usd: 0.0,
+            volume_h1_usd: 0.0,
+            fdv_usd: 0.0,
+            price_change_m5: 0.0,
+            price_change_m15: 0.0,
+            price_change_h1: 0.0,
+            price_change_h6: 0.0,
+            price_change_h24: 0.0,
+            buy_pressure_ratio: 0.0,
+            buy_pressure_m15: 0.0,
+            buy_pressure_h1: 0.0,
+            buy_pressure_h6: 0.0,
+            buy_pressure_h24: 0.0,
+            holder_count: None,
+            locked_liquidity_ratio: None,
+            contract_renounced_score: None,
+            pair_created_at: OffsetDateTime::now_utc(),
+            dex_id: "uniswap".to_string(),
+            pool_labels: labels.iter().map(|label| label.to_string()).collect(),
+            confidence: 0.0,
+            safety_flags: Vec::new(),
+            usd_per_base: 0.0,
+            alternate_pools: Vec::new(),
+        }
+    }
+
+    /// Buys UNI with ETH through the V2 and V3 routers of a mainnet fork and
+    /// quotes the sale back. Run with
+    /// `ANVIL_FORK_URL=<mainnet rpc> cargo test -- --ignored`.
+    #[tokio::test]
+    #[ignore = "needs anvil on PATH and ANVIL_FORK_URL"]
+    async fn swaps_through_v2_and_v3_routers_on_anvil_fork() -> Result<()> {
+        let fork_url = std::env::var("ANVIL_FORK_URL").context("ANVIL_FORK_URL not set")?;
+        let anvil = Anvil::new().fork(fork_url).spawn();
+        let trader = Trader::new(fork_config(&anvil.endpoint()), HttpClient::default()).await?;
+        let weth: Address = WETH.parse()?;
+        let uni: Address = UNI.parse()?;
+
+        for candidate in [
+            uni_candidate(UNI_WETH_V2, &["v2"]),
+            uni_candidate(UNI_WETH_V3, &["v3"]),
+        ] {
+            let size = parse_ether("0.1")?;
+            let route = trader.resolve_route(&candidate, size).await?;
+            assert_eq!(
+                matches!(route.pool, PoolKind::V3 { .. }),
+                candidate.is_concentrated_liquidity()
+            );
+
+            let sized = trader
+                .fit_price_impact(candidate.pair_address, weth, uni, size, route)
+                .await?
+                .expect("0.1 ETH fits the impact limit");
+            let execution = trader.execute_entry(&uni, sized, &candidate, route).await?;
+            assert!(!execution.tokens_acquired.is_zero());
+
+            let back = trader
+                .quote_sell(&uni, execution.tokens_acquired, weth, route)
+                .await?;
+            assert!(!back.is_zero() && back < sized.amount_in);
+        }
+        Ok(())
+    }
+
+    /// Chain state behind the mock node, which also signs as the trader's
+    /// remote signer.
+    #[derive(Default)]
+    struct MockChain {
+        block: u64,
+        /// Whether transactions sent through the relay get a receipt.
+        includes_private: bool,
+        public_txs: Vec<Bytes>,
+    }
+
+    type Node = Arc<Mutex<MockChain>>;
+
+    /// JSON-RPC node whose block number advances on every poll, starting
+    /// from 100.
+    async fn mock_node(includes_private: bool) -> (String, Node) {
+        let node = Node::new(Mutex::new(MockChain {
+            block: 99,
+            includes_private,
+            ..MockChain::default()
+        }));
+        let app = Router::new()
+            .route("/", post(node_request))
+            .with_state(node.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (url, node)
+    }
+
+    async fn node_request(State(node): State<Node>, Json(request): Json<Value>) -> Json<Value> {
+        let wallet = KEY.parse::<LocalWallet>().unwrap().with_chain_id(1u64);
+        let params = &request["params"];
+        let mut chain = node.lock().await;
+        let result = match request["method"].as_str().unwrap_or_default() {
+            "eth_chainId" => json!(U64::one()),
+            "eth_accounts" => json!([wallet.address()]),
+            "eth_blockNumber" => {
+                chain.block += 1;
+                json!(U64::from(chain.block))
+            }
+            "eth_signTransaction" => {
+                let tx: TypedTransaction = serde_json::from_value(params[0].clone()).unwrap();
+                let signature = wallet.sign_transaction_sync(&tx).unwrap();
+                json!(tx.rlp_signed(&signature))
+            }
+            "eth_sign" => {
+                let message: Bytes = serde_json::from_value(params[1].clone()).unwrap();
+                let signature = wallet.sign_message(message.as_ref()).await.unwrap();
+                json!(Bytes::from(signature.to_vec()))
+            }
+            "eth_getTransactionReceipt" if chain.includes_private => json!(TransactionReceipt {
+                transaction_hash: serde_json::from_value(params[0].clone()).unwrap(),
+                block_number: Some(chain.block.into()),
+                ..TransactionReceipt::default()
+            }),
+            "eth_getTransactionReceipt" => Value::Null,
+            "eth_sendRawTransaction" => {
+                let raw: Bytes = serde_json::from_value(params[0].clone()).unwrap();
+                chain.public_txs.push(raw.clone());
+                json!(H256::from(keccak256(&raw)))
+            }
+            method => {
+                return Json(json!({
+                    "jsonrpc": "2.0",
+                    "id": request["id"],
+                    "error": { "code": -32601, "message": format!("{method} is not mocked") }
+                }));
+            }
+        };
+        Json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }))
+    }
+
+    async fn relayed_trader(rpc_url: &str, relay_url: &str, fallback_to_public: bool) -> Trader {
+        let config = serde_json::from_value(json!({
+            "chain": "mainnet",
+            "rpc": { "http_urls": [rpc_url], "poll_interval_ms": 10, "max_retries": 0 },
+            "strategy": { "max_positions": 1, "position_size_eth": 0.1 },
+            "exchange": {
+                "venues": [{
+                    "dex_id": "uniswap",
+                    "protocol": "uniswap_v2",
+                    "router_address": "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D"
+                }],
+                "private_relay": {
+                    "url": relay_url,
+                    "method": "eth_sendPrivateTransaction",
+                    "max_blocks": 3,
+                    "fallback_to_public": fallback_to_public
+                }
+            },
+            "risk": {},
+            "signer": { "kind": "remote", "url": rpc_url }
+        }))
+        .unwrap();
+        Trader::new(config, HttpClient::default()).await.unwrap()
+    }
+
+    /// A fully specified transfer, so filling it needs no gas or nonce calls.
+    fn transfer() -> TypedTransaction {
+        TransactionRequest::new()
+            .to(Address::zero())
+            .value(1)
+            .gas(21_000)
+            .gas_price(1_000_000_000)
+            .nonce(0)
+            .into()
+    }
+
+    /// The raw transaction each relay request carried.
+    fn relayed_txs(received: &Received) -> Vec<Bytes> {
+        received
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_, request)| {
+                assert_eq!(request["method"], "eth_sendPrivateTransaction");
+                serde_json::from_value(request["params"][0]["tx"].clone()).unwrap()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn broadcast_returns_the_relayed_transaction_once_it_lands() {
+        let (rpc_url, node) = mock_node(true).await;
+        let (relay_url, received) = stand_in_relay().await;
+        let trader = relayed_trader(&rpc_url, &relay_url, true).await;
+
+        let pending = trader
+            .broadcast(&trader.wallets[0], transfer())
+            .await
+            .unwrap();
+
+        let relayed = relayed_txs(&received);
+        assert_eq!(relayed.len(), 1);
+        assert_eq!(pending.tx_hash(), H256::from(keccak256(&relayed[0])));
+        assert!(node.lock().await.public_txs.is_empty());
+    }
+
+    #[tokio::test]
+    async fn broadcast_falls_back_to_the_public_mempool_after_the_window() {
+        let (rpc_url, node) = mock_node(false).await;
+        let (relay_url, received) = stand_in_relay().await;
+        let trader = relayed_trader(&rpc_url, &relay_url, true).await;
+
+        let pending = trader
+            .broadcast(&trader.wallets[0], transfer())
+            .await
+            .unwrap();
+
+        let relayed = relayed_txs(&received);
+        let node = node.lock().await;
+        // Submitted at block 100 and polled until block 104 passed the
+        // three-block window.
+        assert_eq!(node.block, 104);
+        assert_eq!(node.public_txs, relayed);
+        assert_eq!(pending.tx_hash(), H256::from(keccak256(&relayed[0])));
+    }
+
+    #[tokio::test]
+    async fn broadcast_fails_without_public_fallback() {
+        let (rpc_url, node) = mock_node(false).await;
+        let (relay_url, received) = stand_in_relay().await;
+        let trader = relayed_trader(&rpc_url, &relay_url, false).await;
+
+        let err = trader
+            .broadcast(&trader.wallets[0], transfer())
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("public fallback disabled"));
+        assert_eq!(relayed_txs(&received).len(), 1);
+        assert!(node.lock().await.public_txs.is_empty());
+    }
 }
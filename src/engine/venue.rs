@@ -0,0 +1,422 @@
+use std::sync::Arc;
+
+use anyhow::{Context, Result, anyhow, bail};
+use ethers::{prelude::*, providers::Provider, types::transaction::eip2718::TypedTransaction};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::config::{VenueConfig, VenueProtocol};
+
+use super::{rpc::RpcPool, scanner::GemCandidate, trader::SigningMiddleware};
+
+abigen!(
+    UniswapV2Router,
+    r#"[
+        {"inputs":[{"internalType":"uint256","name":"amountIn","type":"uint256"},{"internalType":"address[]","name":"path","type":"address[]"}],"name":"getAmountsOut","outputs":[{"internalType":"uint256[]","name":"","type":"uint256[]"}],"stateMutability":"view","type":"function"},
+        {"inputs":[{"internalType":"uint256","name":"amountOutMin","type":"uint256"},{"internalType":"address[]","name":"path","type":"address[]"},{"internalType":"address","name":"to","type":"address"},{"internalType":"uint256","name":"deadline","type":"uint256"}],"name":"swapExactETHForTokensSupportingFeeOnTransferTokens","outputs":[],"stateMutability":"payable","type":"function"},
+        {"inputs":[{"internalType":"uint256","name":"amountIn","type":"uint256"},{"internalType":"uint256","name":"amountOutMin","type":"uint256"},{"internalType":"address[]","name":"path","type":"address[]"},{"internalType":"address","name":"to","type":"address"},{"internalType":"uint256","name":"deadline","type":"uint256"}],"name":"swapExactTokensForETHSupportingFeeOnTransferTokens","outputs":[],"stateMutability":"nonpayable","type":"function"}
+    ]"#,
+    event_derives(serde::Deserialize, serde::Serialize)
+);
+
+abigen!(
+    SolidlyRouter,
+    r#"[
+        {"inputs":[{"internalType":"uint256","name":"amountIn","type":"uint256"},{"components":[{"internalType":"address","name":"from","type":"address"},{"internalType":"address","name":"to","type":"address"},{"internalType":"bool","name":"stable","type":"bool"},{"internalType":"address","name":"factory","type":"address"}],"internalType":"struct IRouter.Route[]","name":"routes","type":"tuple[]"}],"name":"getAmountsOut","outputs":[{"internalType":"uint256[]","name":"amounts","type":"uint256[]"}],"stateMutability":"view","type":"function"},
+        {"inputs":[{"internalType":"uint256","name":"amountOutMin","type":"uint256"},{"components":[{"internalType":"address","name":"from","type":"address"},{"internalType":"address","name":"to","type":"address"},{"internalType":"bool","name":"stable","type":"bool"},{"internalType":"address","name":"factory","type":"address"}],"internalType":"struct IRouter.Route[]","name":"routes","type":"tuple[]"},{"internalType":"address","name":"to","type":"address"},{"internalType":"uint256","name":"deadline","type":"uint256"}],"name":"swapExactETHForTokensSupportingFeeOnTransferTokens","outputs":[],"stateMutability":"payable","type":"function"},
+        {"inputs":[{"internalType":"uint256","name":"amountIn","type":"uint256"},{"internalType":"uint256","name":"amountOutMin","type":"uint256"},{"components":[{"internalType":"address","name":"from","type":"address"},{"internalType":"address","name":"to","type":"address"},{"internalType":"bool","name":"stable","type":"bool"},{"internalType":"address","name":"factory","type":"address"}],"internalType":"struct IRouter.Route[]","name":"routes","type":"tuple[]"},{"internalType":"address","name":"to","type":"address"},{"internalType":"uint256","name":"deadline","type":"uint256"}],"name":"swapExactTokensForETHSupportingFeeOnTransferTokens","outputs":[],"stateMutability":"nonpayable","type":"function"}
+    ]"#
+);
+
+abigen!(
+    UniswapV3Quoter,
+    r#"[
+        {"inputs":[{"internalType":"address","name":"tokenIn","type":"address"},{"internalType":"address","name":"tokenOut","type":"address"},{"internalType":"uint24","name":"fee","type":"uint24"},{"internalType":"uint256","name":"amountIn","type":"uint256"},{"internalType":"uint160","name":"sqrtPriceLimitX96","type":"uint160"}],"name":"quoteExactInputSingle","outputs":[{"internalType":"uint256","name":"amountOut","type":"uint256"}],"stateMutability":"nonpayable","type":"function"}
+    ]"#
+);
+
+abigen!(
+    UniswapV3SwapRouter,
+    r#"[
+        {"inputs":[{"components":[{"internalType":"address","name":"tokenIn","type":"address"},{"internalType":"address","name":"tokenOut","type":"address"},{"internalType":"uint24","name":"fee","type":"uint24"},{"internalType":"address","name":"recipient","type":"address"},{"internalType":"uint256","name":"deadline","type":"uint256"},{"internalType":"uint256","name":"amountIn","type":"uint256"},{"internalType":"uint256","name":"amountOutMinimum","type":"uint256"},{"internalType":"uint160","name":"sqrtPriceLimitX96","type":"uint160"}],"internalType":"struct ISwapRouter.ExactInputSingleParams","name":"params","type":"tuple"}],"name":"exactInputSingle","outputs":[{"internalType":"uint256","name":"amountOut","type":"uint256"}],"stateMutability":"payable","type":"function"},
+        {"inputs":[{"internalType":"uint256","name":"amountMinimum","type":"uint256"},{"internalType":"address","name":"recipient","type":"address"}],"name":"unwrapWETH9","outputs":[],"stateMutability":"payable","type":"function"},
+        {"inputs":[{"internalType":"bytes[]","name":"data","type":"bytes[]"}],"name":"multicall","outputs":[{"internalType":"bytes[]","name":"results","type":"bytes[]"}],"stateMutability":"payable","type":"function"}
+    ]"#
+);
+
+abigen!(
+    LiquidityPool,
+    r#"[
+        {"inputs":[],"name":"factory","outputs":[{"internalType":"address","name":"","type":"address"}],"stateMutability":"view","type":"function"},
+        {"inputs":[],"name":"fee","outputs":[{"internalType":"uint24","name":"","type":"uint24"}],"stateMutability":"view","type":"function"},
+        {"inputs":[],"name":"stable","outputs":[{"internalType":"bool","name":"","type":"bool"}],"stateMutability":"view","type":"function"},
+        {"inputs":[],"name":"token0","outputs":[{"internalType":"address","name":"","type":"address"}],"stateMutability":"view","type":"function"},
+        {"inputs":[],"name":"getReserves","outputs":[{"internalType":"uint256","name":"reserve0","type":"uint256"},{"internalType":"uint256","name":"reserve1","type":"uint256"},{"internalType":"uint256","name":"blockTimestampLast","type":"uint256"}],"stateMutability":"view","type":"function"},
+        {"inputs":[],"name":"slot0","outputs":[{"internalType":"uint160","name":"sqrtPriceX96","type":"uint160"},{"internalType":"int24","name":"tick","type":"int24"},{"internalType":"uint16","name":"observationIndex","type":"uint16"},{"internalType":"uint16","name":"observationCardinality","type":"uint16"},{"internalType":"uint16","name":"observationCardinalityNext","type":"uint16"},{"internalType":"uint8","name":"feeProtocol","type":"uint8"},{"internalType":"bool","name":"unlocked","type":"bool"}],"stateMutability":"view","type":"function"}
+    ]"#
+);
+
+/// Pool flavour within a venue.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PoolKind {
+    #[default]
+    V2,
+    Solidly {
+        stable: bool,
+    },
+    V3 {
+        fee: u32,
+    },
+}
+
+/// Venue and pool a swap is routed through. Positions remember the route they
+/// were opened on so exits hit the same pool. Routes persisted before the venue
+/// registry existed carry a zero router and fall back to the first venue of the
+/// matching protocol.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SwapRoute {
+    #[serde(default)]
+    pub router: Address,
+    #[serde(flatten)]
+    pub pool: PoolKind,
+}
+
+/// Amounts and addresses for a single-hop swap.
+pub struct SwapLeg {
+    pub token_in: Address,
+    pub token_out: Address,
+    pub amount_in: U256,
+    pub min_out: U256,
+    pub recipient: Address,
+    pub deadline: U256,
+}
+
+enum VenueClient {
+    UniswapV2(UniswapV2Router<SigningMiddleware>),
+    Solidly {
+        router: SolidlyRouter<SigningMiddleware>,
+        factory: Address,
+    },
+    UniswapV3 {
+        quoter: UniswapV3Quoter<SigningMiddleware>,
+        router: UniswapV3SwapRouter<SigningMiddleware>,
+    },
+}
+
+pub struct Venue {
+    pub dex_id: String,
+    pub router_address: Address,
+    factory_address: Option<Address>,
+    fee_tiers: Vec<u32>,
+    client: VenueClient,
+}
+
+impl Venue {
+    fn new(config: &VenueConfig, client: Arc<SigningMiddleware>) -> Result<Self> {
+        let venue_client = match config.protocol {
+            VenueProtocol::UniswapV2 => {
+                VenueClient::UniswapV2(UniswapV2Router::new(config.router_address, client))
+            }
+            VenueProtocol::Solidly => VenueClient::Solidly {
+                router: SolidlyRouter::new(config.router_address, client),
+                factory: config
+                    .factory_address
+                    .ok_or_else(|| anyhow!("solidly venue {} needs a factory", config.dex_id))?,
+            },
+            VenueProtocol::UniswapV3 => VenueClient::UniswapV3 {
+                quoter: UniswapV3Quoter::new(
+                    config
+                        .quoter_address
+                        .ok_or_else(|| anyhow!("v3 venue {} needs a quoter", config.dex_id))?,
+                    client.clone(),
+                ),
+                router: UniswapV3SwapRouter::new(config.router_address, client),
+            },
+        };
+
+        Ok(Self {
+            dex_id: config.dex_id.clone(),
+            router_address: config.router_address,
+            factory_address: config.factory_address,
+            fee_tiers: config.fee_tiers.clone(),
+            client: venue_client,
+        })
+    }
+
+    pub fn protocol(&self) -> VenueProtocol {
+        match self.client {
+            VenueClient::UniswapV2(_) => VenueProtocol::UniswapV2,
+            VenueClient::Solidly { .. } => VenueProtocol::Solidly,
+            VenueClient::UniswapV3 { .. } => VenueProtocol::UniswapV3,
+        }
+    }
+
+    pub async fn quote(
+        &self,
+        token_in: Address,
+        token_out: Address,
+        amount_in: U256,
+        pool: PoolKind,
+    ) -> Result<U256> {
+        let amounts = match (&self.client, pool) {
+            (VenueClient::UniswapV2(router), PoolKind::V2) => router
+                .get_amounts_out(amount_in, vec![token_in, token_out])
+                .call()
+                .await
+                .context("router getAmountsOut")?,
+            (VenueClient::Solidly { router, factory }, PoolKind::Solidly { stable }) => router
+                .get_amounts_out(
+                    amount_in,
+                    vec![Route {
+                        from: token_in,
+                        to: token_out,
+                        stable,
+                        factory: *factory,
+                    }],
+                )
+                .call()
+                .await
+                .context("solidly router getAmountsOut")?,
+            (VenueClient::UniswapV3 { quoter, .. }, PoolKind::V3 { fee }) => {
+                return quoter
+                    .quote_exact_input_single(token_in, token_out, fee, amount_in, U256::zero())
+                    .call()
+                    .await
+                    .context("v3 quoter quoteExactInputSingle");
+            }
+            _ => bail!("{pool:?} pool cannot be quoted on {} venue", self.dex_id),
+        };
+        amounts
+            .last()
+            .cloned()
+            .ok_or_else(|| anyhow!("router getAmountsOut returned empty path"))
+    }
+
+    /// Swap paying `leg.amount_in` of the native coin for `leg.token_out`.
+    pub fn buy_with_native_tx(&self, leg: &SwapLeg, pool: PoolKind) -> Result<TypedTransaction> {
+        let mut tx = match (&self.client, pool) {
+            (VenueClient::UniswapV2(router), PoolKind::V2) => {
+                router
+                    .swap_exact_eth_for_tokens_supporting_fee_on_transfer_tokens(
+                        leg.min_out,
+                        vec![leg.token_in, leg.token_out],
+                        leg.recipient,
+                        leg.deadline,
+                    )
+                    .tx
+            }
+            (VenueClient::Solidly { router, factory }, PoolKind::Solidly { stable }) => {
+                router
+                    .swap_exact_eth_for_tokens_supporting_fee_on_transfer_tokens(
+                        leg.min_out,
+                        vec![Route {
+                            from: leg.token_in,
+                            to: leg.token_out,
+                            stable,
+                            factory: *factory,
+                        }],
+                        leg.recipient,
+                        leg.deadline,
+                    )
+                    .tx
+            }
+            (VenueClient::UniswapV3 { router, .. }, PoolKind::V3 { fee }) => {
+                router
+                    .exact_input_single(ExactInputSingleParams {
+                        token_in: leg.token_in,
+                        token_out: leg.token_out,
+                        fee,
+                        recipient: leg.recipient,
+                        deadline: leg.deadline,
+                        amount_in: leg.amount_in,
+                        amount_out_minimum: leg.min_out,
+                        sqrt_price_limit_x96: U256::zero(),
+                    })
+                    .tx
+            }
+            _ => bail!("{pool:?} pool cannot be traded on {} venue", self.dex_id),
+        };
+        tx.set_value(leg.amount_in);
+        Ok(tx)
+    }
+
+    /// Swap selling `leg.amount_in` of `leg.token_in` for the native coin.
+    pub fn sell_for_native_tx(&self, leg: &SwapLeg, pool: PoolKind) -> Result<TypedTransaction> {
+        let tx = match (&self.client, pool) {
+            (VenueClient::UniswapV2(router), PoolKind::V2) => {
+                router
+                    .swap_exact_tokens_for_eth_supporting_fee_on_transfer_tokens(
+                        leg.amount_in,
+                        leg.min_out,
+                        vec![leg.token_in, leg.token_out],
+                        leg.recipient,
+                        leg.deadline,
+                    )
+                    .tx
+            }
+            (VenueClient::Solidly { router, factory }, PoolKind::Solidly { stable }) => {
+                router
+                    .swap_exact_tokens_for_eth_supporting_fee_on_transfer_tokens(
+                        leg.amount_in,
+                        leg.min_out,
+                        vec![Route {
+                            from: leg.token_in,
+                            to: leg.token_out,
+                            stable,
+                            factory: *factory,
+                        }],
+                        leg.recipient,
+                        leg.deadline,
+                    )
+                    .tx
+            }
+            (VenueClient::UniswapV3 { router, .. }, PoolKind::V3 { fee }) => {
+                // The swap router keeps the wrapped output and unwraps it to the
+                // recipient in the same multicall so exits settle in the native coin.
+                let swap = router
+                    .exact_input_single(ExactInputSingleParams {
+                        token_in: leg.token_in,
+                        token_out: leg.token_out,
+                        fee,
+                        recipient: router.address(),
+                        deadline: leg.deadline,
+                        amount_in: leg.amount_in,
+                        amount_out_minimum: leg.min_out,
+                        sqrt_price_limit_x96: U256::zero(),
+                    })
+                    .calldata()
+                    .context("encode exactInputSingle call")?;
+                let unwrap = router
+                    .unwrap_weth9(leg.min_out, leg.recipient)
+                    .calldata()
+                    .context("encode unwrapWETH9 call")?;
+                router.multicall(vec![swap, unwrap]).tx
+            }
+            _ => bail!("{pool:?} pool cannot be traded on {} venue", self.dex_id),
+        };
+        Ok(tx)
+    }
+
+    async fn best_fee_tier(&self, candidate: &GemCandidate, amount_in: U256) -> Result<u32> {
+        let mut best: Option<(u32, U256)> = None;
+        for fee in &self.fee_tiers {
+            let Ok(quote) = self
+                .quote(
+                    candidate.base_token,
+                    candidate.token_address,
+                    amount_in,
+                    PoolKind::V3 { fee: *fee },
+                )
+                .await
+            else {
+                continue;
+            };
+            if best.is_none_or(|(_, best_quote)| quote > best_quote) {
+                best = Some((*fee, quote));
+            }
+        }
+
+        best.map(|(fee, _)| fee).ok_or_else(|| {
+            anyhow!(
+                "no v3 fee tier quoted for pair {:?}",
+                candidate.pair_address
+            )
+        })
+    }
+}
+
+/// Swap venues configured for the chain, keyed by router address.
+pub struct VenueRegistry {
+    venues: Vec<Venue>,
+    provider: Arc<Provider<RpcPool>>,
+}
+
+impl VenueRegistry {
+    pub fn new(
+        configs: &[VenueConfig],
+        client: Arc<SigningMiddleware>,
+        provider: Arc<Provider<RpcPool>>,
+    ) -> Result<Self> {
+        let venues = configs
+            .iter()
+            .map(|config| Venue::new(config, client.clone()))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { venues, provider })
+    }
+
+    pub fn get(&self, route: &SwapRoute) -> Result<&Venue> {
+        let protocol = match route.pool {
+            PoolKind::V2 => VenueProtocol::UniswapV2,
+            PoolKind::Solidly { .. } => VenueProtocol::Solidly,
+            PoolKind::V3 { .. } => VenueProtocol::UniswapV3,
+        };
+        self.venues
+            .iter()
+            .find(|venue| {
+                venue.protocol() == protocol
+                    && (route.router.is_zero() || venue.router_address == route.router)
+            })
+            .ok_or_else(|| anyhow!("no registered venue for route {route:?}"))
+    }
+
+    /// Finds the venue the candidate's pair lives on. The pair's own `factory()`
+    /// is authoritative; DexScreener's `dex_id` is only trusted for venues
+    /// registered without a factory.
+    pub async fn resolve(&self, candidate: &GemCandidate, amount_in: U256) -> Result<SwapRoute> {
+        let pool = LiquidityPool::new(candidate.pair_address, self.provider.clone());
+        let pair_factory = pool.factory().call().await.ok();
+        let concentrated = candidate.is_concentrated_liquidity();
+
+        let venue = pair_factory
+            .and_then(|factory| {
+                self.venues
+                    .iter()
+                    .find(|venue| venue.factory_address == Some(factory))
+            })
+            .or_else(|| {
+                self.venues.iter().find(|venue| {
+                    venue.dex_id.eq_ignore_ascii_case(&candidate.dex_id)
+                        && (venue.protocol() == VenueProtocol::UniswapV3) == concentrated
+                        && (venue.factory_address.is_none() || pair_factory.is_none())
+                })
+            })
+            .ok_or_else(|| {
+                anyhow!(
+                    "pair {:?} on {} (factory {pair_factory:?}) is not on a registered venue",
+                    candidate.pair_address,
+                    candidate.dex_id
+                )
+            })?;
+
+        let pool_kind = match venue.protocol() {
+            VenueProtocol::UniswapV2 => PoolKind::V2,
+            VenueProtocol::Solidly => PoolKind::Solidly {
+                stable: pool
+                    .stable()
+                    .call()
+                    .await
+                    .context("read solidly pool stable flag")?,
+            },
+            VenueProtocol::UniswapV3 => match pool.fee().call().await {
+                Ok(fee) => PoolKind::V3 { fee },
+                Err(_) => PoolKind::V3 {
+                    fee: venue.best_fee_tier(candidate, amount_in).await?,
+                },
+            },
+        };
+
+        debug!(
+            pair = ?candidate.pair_address,
+            venue = %venue.dex_id,
+            router = ?venue.router_address,
+            pool = ?pool_kind,
+            "resolved swap venue"
+        );
+
+        Ok(SwapRoute {
+            router: venue.router_address,
+            pool: pool_kind,
+        })
+    }
+}
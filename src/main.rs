@@ -1,18 +1,25 @@
 mod api;
 mod config;
 mod engine;
+mod mock;
 
-use std::sync::Arc;
+use std::{net::SocketAddr, path::PathBuf, sync::Arc};
 
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
 use ethers::types::Address;
+use time::format_description::well_known::Rfc3339;
 use tracing_subscriber::EnvFilter;
 
 use crate::{
     config::BotConfig,
     engine::{
         TradingBot,
+        backtest::Backtester,
+        dataset::{self, SnapshotWriter},
+        http::HttpClient,
+        optimize::{Optimizer, SearchSpace},
+        recorder::Recorder,
         risk::TokenRiskReport,
         scanner::{DexScreenerScanner, GemCandidate},
     },
@@ -31,13 +38,91 @@ enum Command {
     Run {
         #[arg(long)]
         once: bool,
+        /// Sell all open positions after a shutdown signal
+        #[arg(long)]
+        flatten_on_exit: bool,
     },
     /// Scan current market for memecoin opportunities
     Scan,
     /// Evaluate a specific token contract address
-    Evaluate { token: Address },
+    Evaluate {
+        token: Address,
+        /// Print the risk reports, with their rule breakdown, as JSON
+        #[arg(long)]
+        json: bool,
+    },
     /// Perform a health check against the configured RPC
     Health,
+    /// Zero router allowances for tokens no longer held
+    RevokeApprovals {
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Show native balances and open positions per trading wallet
+    Wallets,
+    /// Replay recorded market snapshots through the strategy
+    Backtest {
+        /// Snapshot file (`.jsonl` or `.jsonl.gz`) or directory of them
+        data: PathBuf,
+        #[arg(long, default_value_t = 10_000.0)]
+        starting_equity_usd: f64,
+        /// Write the full report, including every trade, as JSON
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Search strategy and risk parameters over recorded snapshots
+    Optimize {
+        /// Snapshot file (`.jsonl` or `.jsonl.gz`) or directory of them
+        data: PathBuf,
+        /// TOML file with a `[parameters]` table of ranges
+        #[arg(long)]
+        space: PathBuf,
+        /// Draw this many random parameter sets instead of the full grid
+        #[arg(long)]
+        random: Option<usize>,
+        /// Seed for reproducible random search
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Runs with fewer trades are ranked last
+        #[arg(long, default_value_t = 5)]
+        min_trades: usize,
+        /// Backtests run in parallel; defaults to the number of CPUs
+        #[arg(long)]
+        jobs: Option<usize>,
+        #[arg(long, default_value_t = 10_000.0)]
+        starting_equity_usd: f64,
+        /// Rows of the ranked table to print
+        #[arg(long, default_value_t = 20)]
+        top: usize,
+        /// Write every result as JSON
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Record raw market data into compressed snapshot files for backtests
+    Record {
+        #[arg(long, default_value = "data/snapshots")]
+        output_dir: PathBuf,
+        /// Seconds between snapshots; defaults to the discovery interval
+        #[arg(long)]
+        interval_secs: Option<u64>,
+        /// Start a new file after this many minutes
+        #[arg(long, default_value_t = 60)]
+        rotate_minutes: u64,
+        /// Keep recording a pair for this long after discovery last listed it
+        #[arg(long, default_value_t = 240)]
+        track_minutes: u64,
+        /// Also record router quotes for the position size (needs a signer)
+        #[arg(long)]
+        with_quotes: bool,
+    },
+    /// Serve DexScreener, GoPlus and DefiLlama responses from a fixture
+    MockApi {
+        /// JSON fixture; defaults to the bundled `fixtures/mock_market.json`
+        #[arg(long)]
+        fixture: Option<PathBuf>,
+        #[arg(long, default_value = "127.0.0.1:8900")]
+        addr: SocketAddr,
+    },
 }
 
 #[tokio::main]
@@ -46,28 +131,88 @@ async fn main() -> Result<()> {
     init_tracing();
 
     let cli = Cli::parse();
+    // The mock serves fixtures only and needs no bot configuration.
+    if let Command::MockApi { fixture, addr } = &cli.command {
+        return mock::run(mock::MockFixture::load(fixture.as_deref())?, *addr).await;
+    }
     let config = BotConfig::from_env()?;
 
     match cli.command {
-        Command::Run { once } => run_bot(config, once).await,
+        Command::Run {
+            once,
+            flatten_on_exit,
+        } => run_bot(config, once, flatten_on_exit).await,
         Command::Scan => scan_market(config).await,
-        Command::Evaluate { token } => evaluate_token(config, token).await,
+        Command::Evaluate { token, json } => evaluate_token(config, token, json).await,
         Command::Health => run_health_check(config).await,
+        Command::RevokeApprovals { dry_run } => revoke_approvals(config, dry_run).await,
+        Command::Wallets => show_wallets(config).await,
+        Command::Backtest {
+            data,
+            starting_equity_usd,
+            output,
+        } => run_backtest(config, data, starting_equity_usd, output).await,
+        Command::Optimize {
+            data,
+            space,
+            random,
+            seed,
+            min_trades,
+            jobs,
+            starting_equity_usd,
+            top,
+            output,
+        } => {
+            let jobs = jobs.unwrap_or_else(|| {
+                std::thread::available_parallelism().map_or(1, |cpus| cpus.get())
+            });
+            let optimizer = Optimizer::new(config, starting_equity_usd, min_trades, jobs);
+            run_optimize(optimizer, data, space, random, seed, top, output).await
+        }
+        Command::Record {
+            output_dir,
+            interval_secs,
+            rotate_minutes,
+            track_minutes,
+            with_quotes,
+        } => {
+            let interval_secs = interval_secs.unwrap_or(config.strategy.discovery_interval_secs);
+            record_market(
+                config,
+                output_dir,
+                interval_secs,
+                rotate_minutes,
+                track_minutes,
+                with_quotes,
+            )
+            .await
+        }
+        Command::MockApi { .. } => unreachable!("handled before loading the config"),
     }
 }
 
-async fn run_bot(config: BotConfig, once: bool) -> Result<()> {
+async fn run_bot(config: BotConfig, once: bool, flatten_on_exit: bool) -> Result<()> {
     let bot = Arc::new(TradingBot::new(config.clone()).await?);
 
     if once {
         bot.tick().await
     } else {
+        {
+            let bot = bot.clone();
+            tokio::spawn(async move {
+                shutdown_signal().await;
+                tracing::info!("shutdown signal received");
+                bot.shutdown();
+            });
+        }
+
         let api_handle = {
             let bot = bot.clone();
             let addr = config.monitoring.bind_addr;
             tokio::spawn(async move {
-                if let Err(err) = api::run(bot, addr).await {
+                if let Err(err) = api::run(bot.clone(), addr).await {
                     tracing::error!(error = ?err, "monitoring api terminated");
+                    bot.shutdown();
                 }
             })
         };
@@ -75,23 +220,53 @@ async fn run_bot(config: BotConfig, once: bool) -> Result<()> {
         let trading_handle = {
             let bot = bot.clone();
             tokio::spawn(async move {
-                if let Err(err) = bot.run().await {
+                if let Err(err) = bot.run(flatten_on_exit).await {
                     tracing::error!(error = ?err, "trading loop terminated");
                 }
             })
         };
 
-        tokio::select! {
-            _ = trading_handle => {},
-            _ = api_handle => {},
+        trading_handle.await.context("trading task panicked")?;
+        // The trading loop may have stopped on its own; make sure the API
+        // follows it down.
+        bot.shutdown();
+        api_handle.await.context("monitoring api task panicked")?;
+        Ok(())
+    }
+}
+
+/// Resolves on Ctrl-C or, on Unix, SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            tracing::error!(error = ?err, "failed to listen for ctrl-c");
+            std::future::pending::<()>().await;
         }
+    };
 
-        Ok(())
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(err) => {
+                tracing::error!(error = ?err, "failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
     }
 }
 
 async fn scan_market(config: BotConfig) -> Result<()> {
-    let scanner = DexScreenerScanner::default();
+    let scanner = DexScreenerScanner::new(HttpClient::new(&config.http));
     let candidates = scanner.discover_candidates(&config).await?;
 
     if candidates.is_empty() {
@@ -106,9 +281,10 @@ async fn scan_market(config: BotConfig) -> Result<()> {
     Ok(())
 }
 
-async fn evaluate_token(config: BotConfig, token: Address) -> Result<()> {
-    let scanner = DexScreenerScanner::default();
-    let risk = engine::risk::RiskAnalyzer::new(config.clone());
+async fn evaluate_token(config: BotConfig, token: Address, json: bool) -> Result<()> {
+    let http = HttpClient::new(&config.http);
+    let scanner = DexScreenerScanner::new(http.clone());
+    let risk = engine::risk::RiskAnalyzer::new(config.clone(), http)?;
 
     let candidates = scanner
         .fetch_token_candidates(&token, &config)
@@ -120,10 +296,17 @@ async fn evaluate_token(config: BotConfig, token: Address) -> Result<()> {
         return Ok(());
     }
 
+    let mut reports = Vec::with_capacity(candidates.len());
     for candidate in candidates {
         let report = risk.evaluate_candidate(&candidate).await?;
-        print_candidate(&candidate);
-        print_risk(&report);
+        if !json {
+            print_candidate(&candidate);
+            print_risk(&report);
+        }
+        reports.push(report);
+    }
+    if json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
     }
 
     Ok(())
@@ -133,6 +316,117 @@ async fn run_health_check(config: BotConfig) -> Result<()> {
     let bot = TradingBot::new(config).await?;
     let status = bot.health_check().await?;
     println!("Health: {status}");
+    for endpoint in bot.rpc_status() {
+        println!(
+            "- {} | {} | requests {} | failures {} | latency {}",
+            endpoint.url,
+            if endpoint.healthy {
+                "healthy"
+            } else {
+                "benched"
+            },
+            endpoint.total_requests,
+            endpoint.total_failures,
+            endpoint
+                .latency_ms
+                .map(|ms| format!("{ms:.0}ms"))
+                .unwrap_or_else(|| "n/a".to_string()),
+        );
+    }
+    Ok(())
+}
+
+async fn revoke_approvals(config: BotConfig, dry_run: bool) -> Result<()> {
+    let bot = TradingBot::new(config).await?;
+    let revoked = bot.revoke_approvals(dry_run).await?;
+
+    if revoked.is_empty() {
+        println!("No recorded approvals to revoke.");
+        return Ok(());
+    }
+
+    for approval in revoked {
+        let status = match (
+            &approval.skipped,
+            approval.tx_hash,
+            approval.remaining.is_zero(),
+        ) {
+            (Some(reason), ..) => format!("skipped: {reason}"),
+            (None, Some(tx), _) => format!("revoked in {tx:?}"),
+            (None, None, true) => "already spent".to_string(),
+            (None, None, false) => "would revoke (dry run)".to_string(),
+        };
+        println!(
+            "- wallet {:?} | token {:?} | spender {:?} | allowance {} | {status}",
+            approval.owner, approval.token, approval.spender, approval.remaining
+        );
+    }
+    Ok(())
+}
+
+async fn show_wallets(config: BotConfig) -> Result<()> {
+    let bot = TradingBot::new(config).await?;
+    for wallet in bot.wallet_snapshots().await? {
+        println!(
+            "- {:?} | balance {:.4} | open positions {} | value ${:.2}",
+            wallet.address,
+            wallet.native_balance,
+            wallet.open_positions,
+            wallet.positions_value_usd
+        );
+    }
+    Ok(())
+}
+
+async fn run_backtest(
+    config: BotConfig,
+    data: PathBuf,
+    starting_equity_usd: f64,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let snapshots = dataset::load_snapshots(&data)?;
+    if snapshots.is_empty() {
+        println!("No snapshots found in {data:?}.");
+        return Ok(());
+    }
+
+    let report = Backtester::new(config, starting_equity_usd)?.run(&snapshots)?;
+
+    for trade in &report.trades {
+        println!(
+            "- [{strategy}] {symbol} | {entry} -> {exit} | cost ${cost:.2} | pnl ${pnl:+.2} ({bps:+.0} bps) | {reason:?}",
+            strategy = trade.strategy,
+            symbol = trade.token_symbol,
+            entry = trade.entry_at.format(&Rfc3339).unwrap_or_default(),
+            exit = trade.exit_at.format(&Rfc3339).unwrap_or_default(),
+            cost = trade.cost_usd,
+            pnl = trade.pnl_usd,
+            bps = trade.pnl_bps,
+            reason = trade.reason,
+        );
+    }
+    println!(
+        "{} snapshots | {} trades | win rate {:.1}% | pnl ${:+.2} ({:+.2}%) | max drawdown ${:.2} ({:.2}%) | {} entries skipped for price impact",
+        report.snapshots,
+        report.trades.len(),
+        report.win_rate * 100.0,
+        report.total_pnl_usd,
+        report.return_pct,
+        report.max_drawdown_usd,
+        report.max_drawdown_pct,
+        report.skipped_for_impact,
+    );
+    if report.pnl_by_strategy_usd.len() > 1 {
+        for (strategy, pnl) in &report.pnl_by_strategy_usd {
+            println!("  {strategy}: pnl ${pnl:+.2}");
+        }
+    }
+
+    if let Some(output) = output {
+        std::fs::write(&output, serde_json::to_string_pretty(&report)?)
+            .with_context(|| format!("write backtest report {output:?}"))?;
+        println!("Report written to {output:?}");
+    }
     Ok(())
 }
 
@@ -146,13 +440,41 @@ fn print_candidate(candidate: &GemCandidate) {
         pc = candidate.price_change_h1,
         bp = candidate.buy_pressure_ratio * 100.0,
     );
+    println!(
+        "  pool {pair:?} on {dex}",
+        pair = candidate.pair_address,
+        dex = candidate.dex_id
+    );
+    for pool in &candidate.alternate_pools {
+        println!(
+            "  alternate {pair:?} on {dex} | liquidity ${liquidity:.0}",
+            pair = pool.pair_address,
+            dex = pool.dex_id,
+            liquidity = pool.liquidity_usd,
+        );
+    }
 }
 
 fn print_risk(report: &TokenRiskReport) {
     println!(
-        "  risk score {:.2} | safe: {}",
-        report.score, report.is_safe
+        "  risk score {:.2} / {:.2} | safe: {}",
+        report.score, report.pass_score, report.is_safe
     );
+    for outcome in &report.rules {
+        let observed = outcome
+            .observed
+            .map_or_else(|| "-".to_string(), |value| format!("{value:.2}"));
+        println!(
+            "    {status:<7} {rule:<22} {observed:>14} {op:<2} {threshold:<14.2} {points:>5.2} / {weight:<5.2} {severity}",
+            status = format!("{:?}", outcome.status).to_lowercase(),
+            rule = outcome.rule,
+            op = outcome.op.symbol(),
+            threshold = outcome.threshold,
+            points = outcome.points,
+            weight = outcome.weight,
+            severity = format!("{:?}", outcome.severity).to_lowercase(),
+        );
+    }
     if !report.flags.is_empty() {
         println!("  flags: {}", report.flags.join(", "));
     }
@@ -167,3 +489,97 @@ fn init_tracing() {
         .with_target(false)
         .try_init();
 }
+
+async fn run_optimize(
+    optimizer: Optimizer,
+    data: PathBuf,
+    space: PathBuf,
+    random: Option<usize>,
+    seed: Option<u64>,
+    top: usize,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let snapshots = dataset::load_snapshots(&data)?;
+    if snapshots.is_empty() {
+        println!("No snapshots found in {data:?}.");
+        return Ok(());
+    }
+    let space = SearchSpace::load(&space)?;
+    let param_sets = match random {
+        Some(samples) => space.sample(samples, seed),
+        None => space.grid()?,
+    };
+
+    println!(
+        "Running {} backtests over {} snapshots...",
+        param_sets.len(),
+        snapshots.len()
+    );
+    let results = optimizer.run(param_sets, Arc::new(snapshots)).await?;
+
+    println!(
+        "{:>4} {:>8} {:>9} {:>8} {:>6} {:>6}  params",
+        "rank", "score", "return%", "maxdd%", "trades", "win%"
+    );
+    for (rank, result) in results.iter().take(top).enumerate() {
+        let params = result
+            .params
+            .iter()
+            .map(|(name, value)| format!("{name}={}", (value * 1e4).round() / 1e4))
+            .collect::<Vec<_>>()
+            .join(" ");
+        println!(
+            "{:>4} {:>8.2} {:>+9.2} {:>8.2} {:>6} {:>6.1}  {params}{}",
+            rank + 1,
+            result.score,
+            result.return_pct,
+            result.max_drawdown_pct,
+            result.trades,
+            result.win_rate * 100.0,
+            if result.eligible {
+                ""
+            } else {
+                " (too few trades)"
+            },
+        );
+    }
+
+    if let Some(output) = output {
+        std::fs::write(&output, serde_json::to_string_pretty(&results)?)
+            .with_context(|| format!("write optimization results {output:?}"))?;
+        println!("Results written to {output:?}");
+    }
+    Ok(())
+}
+
+async fn record_market(
+    config: BotConfig,
+    output_dir: PathBuf,
+    interval_secs: u64,
+    rotate_minutes: u64,
+    track_minutes: u64,
+    with_quotes: bool,
+) -> Result<()> {
+    anyhow::ensure!(interval_secs > 0, "--interval-secs must be positive");
+    let mut writer = SnapshotWriter::new(
+        output_dir.clone(),
+        time::Duration::minutes(rotate_minutes as i64),
+    )?;
+    let mut recorder = Recorder::new(
+        config,
+        with_quotes,
+        time::Duration::minutes(track_minutes as i64),
+    )
+    .await?;
+
+    tracing::info!(dir = ?output_dir, interval_secs, with_quotes, "recording market data");
+    recorder
+        .run(
+            &mut writer,
+            std::time::Duration::from_secs(interval_secs),
+            shutdown_signal(),
+        )
+        .await?;
+    tracing::info!("recorder stopped");
+    Ok(())
+}
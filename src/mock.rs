@@ -0,0 +1,252 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::SocketAddr,
+    path::Path,
+    sync::Arc,
+};
+
+use anyhow::{Context, Result};
+use axum::{
+    Json, Router,
+    extract::{Path as UrlPath, Query, State},
+    routing::get,
+};
+use ethers::types::Address;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tracing::info;
+
+/// Fixture served when `mock-api` is started without `--fixture`.
+const DEFAULT_FIXTURE: &str = include_str!("../fixtures/mock_market.json");
+
+/// Canned market data. The `pairs` and `security` fields follow the
+/// recorded snapshot format, so a snapshot line works as a fixture.
+#[derive(Debug, Deserialize)]
+pub struct MockFixture {
+    /// Raw DexScreener pair objects.
+    #[serde(default)]
+    pairs: Vec<Value>,
+    /// Raw GoPlus `token_security` entry per token.
+    #[serde(default)]
+    security: HashMap<Address, Value>,
+    /// USD price per DefiLlama coin key, e.g. `ethereum:0x…`.
+    #[serde(default)]
+    prices: HashMap<String, f64>,
+}
+
+impl MockFixture {
+    /// Reads the fixture at `path`, or the bundled one.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let mut fixture: Self = match path {
+            Some(path) => {
+                let raw = std::fs::read_to_string(path)
+                    .with_context(|| format!("read mock fixture {path:?}"))?;
+                serde_json::from_str(&raw)
+                    .with_context(|| format!("parse mock fixture {path:?}"))?
+            }
+            None => serde_json::from_str(DEFAULT_FIXTURE).context("parse bundled mock fixture")?,
+        };
+        // The bot asks for lowercase `chain:0x…` keys.
+        fixture.prices = fixture
+            .prices
+            .into_iter()
+            .map(|(key, price)| (key.to_lowercase(), price))
+            .collect();
+        Ok(fixture)
+    }
+
+    fn pairs_on(&self, chain: &str) -> Vec<Value> {
+        self.pairs
+            .iter()
+            .filter(|pair| pair.get("chainId").and_then(Value::as_str) == Some(chain))
+            .cloned()
+            .collect()
+    }
+
+    fn pairs_of(&self, token: Address) -> Vec<Value> {
+        self.pairs
+            .iter()
+            .filter(|pair| {
+                ["baseToken", "quoteToken"].iter().any(|side| {
+                    pair.pointer(&format!("/{side}/address"))
+                        .and_then(Value::as_str)
+                        .and_then(|address| address.parse::<Address>().ok())
+                        == Some(token)
+                })
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+/// Serves the DexScreener, GoPlus and DefiLlama endpoints the bot calls from
+/// `fixture`, so every API base URL can point at `addr`.
+pub async fn run(fixture: MockFixture, addr: SocketAddr) -> Result<()> {
+    info!(%addr, "starting mock api");
+    axum::serve(tokio::net::TcpListener::bind(addr).await?, router(fixture))
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+    Ok(())
+}
+
+fn router(fixture: MockFixture) -> Router {
+    Router::new()
+        .route("/latest/dex/trending/:chain", get(chain_pairs))
+        .route("/latest/dex/pairs/:chain", get(chain_pairs))
+        .route("/latest/dex/tokens/:token", get(token_pairs))
+        .route("/api/v1/token_security/:chain_id", get(token_security))
+        .route("/prices/current/:coins", get(prices))
+        .with_state(Arc::new(fixture))
+}
+
+type FixtureState = State<Arc<MockFixture>>;
+
+async fn chain_pairs(State(fixture): FixtureState, UrlPath(chain): UrlPath<String>) -> Json<Value> {
+    Json(json!({ "pairs": fixture.pairs_on(&chain) }))
+}
+
+/// Unknown or malformed tokens get an empty listing, like DexScreener.
+async fn token_pairs(State(fixture): FixtureState, UrlPath(token): UrlPath<String>) -> Json<Value> {
+    let pairs = token
+        .parse::<Address>()
+        .map(|token| fixture.pairs_of(token))
+        .unwrap_or_default();
+    Json(json!({ "pairs": pairs }))
+}
+
+#[derive(Deserialize)]
+struct SecurityQuery {
+    #[serde(default)]
+    contract_addresses: String,
+}
+
+/// Tokens without a fixture entry are left out of `result`, as GoPlus does
+/// for tokens it has not indexed.
+async fn token_security(
+    State(fixture): FixtureState,
+    UrlPath(_chain_id): UrlPath<u64>,
+    Query(query): Query<SecurityQuery>,
+) -> Json<Value> {
+    let result = query
+        .contract_addresses
+        .split(',')
+        .filter_map(|token| token.trim().parse::<Address>().ok())
+        .filter_map(|token| {
+            fixture
+                .security
+                .get(&token)
+                .map(|entry| (format!("{token:?}"), entry.clone()))
+        })
+        .collect::<BTreeMap<_, _>>();
+    Json(json!({ "code": 1, "message": "OK", "result": result }))
+}
+
+async fn prices(State(fixture): FixtureState, UrlPath(coins): UrlPath<String>) -> Json<Value> {
+    let coins = coins
+        .split(',')
+        .filter_map(|key| {
+            let key = key.trim().to_lowercase();
+            fixture
+                .prices
+                .get(&key)
+                .map(|price| (key, json!({ "price": price })))
+        })
+        .collect::<BTreeMap<_, _>>();
+    Json(json!({ "coins": coins }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        config::BotConfig,
+        engine::{http::HttpClient, risk::RiskAnalyzer, scanner::DexScreenerScanner},
+    };
+
+    use super::*;
+
+    const GEM: &str = "0x1111111111111111111111111111111111111111";
+    const GEM_UNISWAP: &str = "0x2222222222222222222222222222222222222222";
+    const GEM_SUSHISWAP: &str = "0x5555555555555555555555555555555555555555";
+    const RUG: &str = "0x3333333333333333333333333333333333333333";
+
+    /// Serves the bundled fixture on a free port and returns a config whose
+    /// API base URLs all point at it.
+    async fn mock_config() -> BotConfig {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let app = router(MockFixture::load(None).unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        serde_json::from_value(json!({
+            "chain": "mainnet",
+            "rpc": { "http_urls": ["http://127.0.0.1:8545"] },
+            "strategy": { "max_positions": 1, "position_size_eth": 0.1 },
+            "exchange": {
+                "venues": [{
+                    "dex_id": "uniswap",
+                    "protocol": "uniswap_v2",
+                    "router_address": "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D"
+                }],
+                "base_tokens": ["0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"]
+            },
+            "risk": {},
+            "api": { "dexscreener_url": url, "goplus_url": url, "defillama_url": url }
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn discovery_keeps_one_pool_per_token_from_the_mock() {
+        let config = mock_config().await;
+        let scanner = DexScreenerScanner::new(HttpClient::default());
+
+        let candidates = scanner.discover_candidates(&config).await.unwrap();
+
+        let mut tokens = candidates
+            .iter()
+            .map(|candidate| candidate.token_address)
+            .collect::<Vec<_>>();
+        tokens.sort();
+        assert_eq!(
+            tokens,
+            [GEM.parse().unwrap(), RUG.parse::<Address>().unwrap()]
+        );
+
+        // The deeper SushiSwap pool has no registered venue.
+        let gem = candidates
+            .iter()
+            .find(|candidate| candidate.token_address == GEM.parse().unwrap())
+            .unwrap();
+        assert_eq!(gem.pair_address, GEM_UNISWAP.parse().unwrap());
+        assert_eq!(gem.alternate_pools.len(), 1);
+        assert_eq!(
+            gem.alternate_pools[0].pair_address,
+            GEM_SUSHISWAP.parse().unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn evaluation_passes_the_gem_and_rejects_the_honeypot() {
+        let config = mock_config().await;
+        let http = HttpClient::default();
+        let scanner = DexScreenerScanner::new(http.clone());
+        let risk = RiskAnalyzer::new(config.clone(), http).unwrap();
+
+        for (token, safe) in [(GEM, true), (RUG, false)] {
+            let token = token.parse().unwrap();
+            let candidates = scanner
+                .fetch_token_candidates(&token, &config)
+                .await
+                .unwrap();
+            assert!(!candidates.is_empty());
+            for candidate in candidates {
+                let report = risk.evaluate_candidate(&candidate).await.unwrap();
+                assert_eq!(report.token, token);
+                assert_eq!(report.is_safe, safe, "{:?}", report.flags);
+                assert!(report.raw_security.is_some());
+            }
+        }
+    }
+}